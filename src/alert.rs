@@ -0,0 +1,145 @@
+//! Subscriptions close to their expiry or over a share of their quota.
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::{backend::Provider, usage::Forecast};
+
+/// When a subscription needs attention.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertThresholds {
+    /// Alert this many days before a subscription expires.
+    pub expiry_days: i64,
+    /// Alert once this share of the quota is used, in percent.
+    pub quota_percent: f64,
+}
+
+impl Default for AlertThresholds {
+    fn default() -> Self {
+        Self {
+            expiry_days: 7,
+            quota_percent: 90.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlertKind {
+    Expired,
+    /// Expires in `days` whole days.
+    Expiring(i64),
+    QuotaExceeded,
+    /// The share of the quota used, in percent.
+    Quota(f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub provider: String,
+    pub kind: AlertKind,
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let provider = &self.provider;
+        match self.kind {
+            AlertKind::Expired => write!(f, "{provider} has expired"),
+            AlertKind::Expiring(0) => write!(f, "{provider} expires today"),
+            AlertKind::Expiring(days) => write!(f, "{provider} expires in {days} days"),
+            AlertKind::QuotaExceeded => write!(f, "{provider} is over its quota"),
+            AlertKind::Quota(percent) => write!(f, "{provider} used {percent:.0}% of its quota"),
+        }
+    }
+}
+
+impl AlertThresholds {
+    /// The alerts of the subscriptions of `providers`, in their order.
+    pub fn check(&self, providers: &[Provider], now: DateTime<Utc>) -> Vec<Alert> {
+        let mut alerts = vec![];
+        for provider in providers {
+            let Some(info) = &provider.subscription_info else {
+                continue;
+            };
+            let forecast = Forecast::new(info, &[], now);
+            let mut alert = |kind| {
+                alerts.push(Alert {
+                    provider: provider.name.clone(),
+                    kind,
+                })
+            };
+            match (forecast.expire, forecast.days_to_expiry(now)) {
+                (Some(expire), _) if expire <= now => alert(AlertKind::Expired),
+                (_, Some(days)) if days < self.expiry_days => alert(AlertKind::Expiring(days)),
+                _ => {}
+            }
+            if let Some(total) = forecast.total.filter(|&total| total > 0) {
+                let percent = forecast.used as f64 * 100.0 / total as f64;
+                if forecast.used > total {
+                    alert(AlertKind::QuotaExceeded);
+                } else if percent >= self.quota_percent {
+                    alert(AlertKind::Quota(percent));
+                }
+            }
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::backend::SubscriptionInfo;
+
+    const GB: u64 = 1 << 30;
+    const DAY: i64 = 24 * 60 * 60;
+
+    fn provider(name: &str, used: u64, expire: i64) -> Provider {
+        Provider {
+            name: name.to_string(),
+            vehicle_type: "HTTP".to_string(),
+            subscription_info: Some(SubscriptionInfo {
+                upload: Some(0),
+                download: Some(used),
+                total: Some(100 * GB),
+                expire: Some(expire),
+            }),
+            updated_at: String::new(),
+            proxies: vec![],
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let now = Utc.timestamp_opt(10 * DAY, 0).unwrap();
+        let providers = [
+            provider("Fine", 10 * GB, 30 * DAY),
+            provider("Soon", 95 * GB, 15 * DAY),
+            provider("Over", 120 * GB, 0),
+            provider("Gone", 0, 9 * DAY),
+        ];
+        let alerts = AlertThresholds::default().check(&providers, now);
+        let alerts: Vec<String> = alerts.iter().map(Alert::to_string).collect();
+        assert_eq!(
+            alerts,
+            [
+                "Soon expires in 5 days",
+                "Soon used 95% of its quota",
+                "Over is over its quota",
+                "Gone has expired",
+            ]
+        );
+
+        let thresholds = AlertThresholds {
+            expiry_days: 30,
+            quota_percent: 100.0,
+        };
+        let alerts = thresholds.check(&providers[..1], now);
+        assert_eq!(alerts[0].kind, AlertKind::Expiring(20));
+        assert_eq!(alerts.len(), 1);
+    }
+}
@@ -0,0 +1,39 @@
+use std::{collections::HashMap, fmt};
+
+use super::{Mode, Provider, ProxyGroup, Traffic, error::Result};
+
+/// The operations the dashboard performs against a clash controller.
+///
+/// [`super::HttpController`] talks to a running controller, while
+/// [`super::MemoryController`] serves data held in memory, e.g. a snapshot.
+pub trait ControllerClient: fmt::Debug + Send + Sync {
+    /// All groups, sorted by name.
+    fn proxy_groups(&self) -> Result<Vec<ProxyGroup>>;
+
+    /// All providers except the builtin ones, sorted by name.
+    fn proxy_providers(&self) -> Result<Vec<Provider>>;
+
+    fn select_proxy(&self, group: &str, proxy: &str) -> Result<()>;
+
+    /// Tests every member of `group`, returning the delay of those that answered.
+    fn latency_test_group(&self, group: &str) -> Result<HashMap<String, u64>>;
+
+    fn latency_test_proxy(&self, proxy: &str) -> Result<u64>;
+
+    fn update_proxy_provider(&self, provider: &str) -> Result<()>;
+
+    /// Runs the healthcheck of `provider`, which tests all of its proxies.
+    fn latency_test_provider(&self, provider: &str) -> Result<()>;
+
+    fn mode(&self) -> Result<Mode>;
+
+    fn set_mode(&self, mode: Mode) -> Result<()>;
+
+    /// Calls `on_sample` with every sample of the traffic stream until it returns `false`.
+    fn traffic(&self, on_sample: &mut dyn FnMut(Traffic) -> bool) -> Result<()>;
+
+    /// Whether every action changing the controller state fails with [`super::Error::ReadOnly`].
+    fn is_read_only(&self) -> bool {
+        false
+    }
+}
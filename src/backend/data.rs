@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use strum_macros::IntoStaticStr;
+use strum_macros::{EnumString, IntoStaticStr};
 
 use super::SelectableProxy;
 
@@ -86,6 +86,7 @@ pub struct HistoryEntry {
 }
 
 /// Additional info under the `extra` map.
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ExtraInfo {
     pub alive: bool,
@@ -142,6 +143,27 @@ impl ProxyType {
     }
 }
 
+/// The proxy mode of the controller, the `mode` field of `/configs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, IntoStaticStr, EnumString)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Mode {
+    Rule,
+    Global,
+    Direct,
+}
+impl Mode {
+    pub fn str(&self) -> &'static str {
+        self.into()
+    }
+}
+
+/// The part of `/configs` in use.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Configs {
+    pub mode: Mode,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ProviderRaw {
     pub name: String,
@@ -155,7 +177,7 @@ pub struct ProviderRaw {
     //   testUrl: string
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct SubscriptionInfo {
     #[serde(rename = "Download")]
     pub download: Option<u64>,
@@ -205,7 +227,7 @@ where
     deserializer.deserialize_map(ProviderVisitor)
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(from = "ProviderRaw")]
 pub struct Provider {
     pub name: String,
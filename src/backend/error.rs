@@ -0,0 +1,47 @@
+use std::{fmt, io};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The request could not be sent or its response could not be decoded.
+    Http(reqwest::Error),
+    /// The controller answered with an error status.
+    Api { status: u16, message: String },
+    /// The controller answered with something that is not what the endpoint should return.
+    Invalid(String),
+    /// The action would change the controller state, but the backend is read-only.
+    ReadOnly,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Http(e) => write!(f, "{e}"),
+            Error::Api { status, message } => write!(f, "controller error {status}: {message}"),
+            Error::Invalid(message) => write!(f, "invalid response: {message}"),
+            Error::ReadOnly => f.write_str("not available in read-only mode"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Http(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(value: reqwest::Error) -> Self {
+        Error::Http(value)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(value: Error) -> Self {
+        io::Error::other(value)
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader},
+};
+
+use reqwest::{
+    Url,
+    blocking::{Client, RequestBuilder, Response},
+};
+use serde::{Deserialize, de::DeserializeOwned};
+
+use super::{
+    ControllerClient, Mode, Provider, ProxyGroup, build_proxy_groups, build_proxy_providers,
+    data::{Configs, ProviderRoot, Root},
+    error::{Error, Result},
+};
+
+const DEFAULT_LATENCY_TEXT_URL: &str = "https://www.gstatic.com/generate_204";
+const TIMEOUT: u64 = 5000;
+
+/// One sample of the `/traffic` stream, in bytes per second.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct Traffic {
+    pub up: u64,
+    pub down: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Delay {
+    delay: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorMessage {
+    message: String,
+}
+
+/// A client for the RESTful API of a running clash controller.
+#[derive(Debug, Clone)]
+pub struct HttpController {
+    base: Url,
+    client: Client,
+}
+
+impl HttpController {
+    pub fn new(base: Url) -> Self {
+        Self {
+            base,
+            client: Client::new(),
+        }
+    }
+
+    pub fn base_url(&self) -> &Url {
+        &self.base
+    }
+
+    /// Builds the url of an endpoint, percent-encoding each segment so that
+    /// names containing `/`, `#` or spaces stay intact.
+    fn url(&self, segments: &[&str]) -> Url {
+        let mut url = self.base.clone();
+        url.path_segments_mut()
+            .expect("controller url cannot be a base")
+            .pop_if_empty()
+            .extend(segments);
+        url
+    }
+
+    fn delay_url(&self, segments: &[&str]) -> Url {
+        let mut url = self.url(segments);
+        url.query_pairs_mut()
+            .append_pair("url", DEFAULT_LATENCY_TEXT_URL)
+            .append_pair("timeout", &TIMEOUT.to_string());
+        url
+    }
+
+    fn send(&self, request: RequestBuilder) -> Result<Response> {
+        let response = request.send()?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let text = response.text().unwrap_or_default();
+        let message = serde_json::from_str::<ErrorMessage>(&text)
+            .map(|e| e.message)
+            .unwrap_or(text);
+        Err(Error::Api {
+            status: status.as_u16(),
+            message,
+        })
+    }
+
+    fn get_json<T: DeserializeOwned>(&self, url: Url) -> Result<T> {
+        Ok(self.send(self.client.get(url))?.json()?)
+    }
+
+    /// Fetches an endpoint as plain json, e.g. `get_raw(&["proxies"])`.
+    pub fn get_raw(&self, segments: &[&str]) -> Result<serde_json::Value> {
+        self.get_json(self.url(segments))
+    }
+}
+
+impl ControllerClient for HttpController {
+    fn proxy_groups(&self) -> Result<Vec<ProxyGroup>> {
+        let response: Root = self.get_json(self.url(&["proxies"]))?;
+        build_proxy_groups(response)
+    }
+
+    fn proxy_providers(&self) -> Result<Vec<Provider>> {
+        let response: ProviderRoot = self.get_json(self.url(&["providers", "proxies"]))?;
+        Ok(build_proxy_providers(response))
+    }
+
+    fn select_proxy(&self, group: &str, proxy: &str) -> Result<()> {
+        let request = self
+            .client
+            .put(self.url(&["proxies", group]))
+            .json(&serde_json::json!({ "name": proxy }));
+        self.send(request)?;
+        Ok(())
+    }
+
+    fn latency_test_group(&self, group: &str) -> Result<HashMap<String, u64>> {
+        self.get_json(self.delay_url(&["group", group, "delay"]))
+    }
+
+    fn latency_test_proxy(&self, proxy: &str) -> Result<u64> {
+        let delay: Delay = self.get_json(self.delay_url(&["proxies", proxy, "delay"]))?;
+        Ok(delay.delay)
+    }
+
+    fn update_proxy_provider(&self, provider: &str) -> Result<()> {
+        let request = self
+            .client
+            .put(self.url(&["providers", "proxies", provider]));
+        self.send(request)?;
+        Ok(())
+    }
+
+    fn latency_test_provider(&self, provider: &str) -> Result<()> {
+        let request = self
+            .client
+            .get(self.url(&["providers", "proxies", provider, "healthcheck"]));
+        self.send(request)?;
+        Ok(())
+    }
+
+    fn mode(&self) -> Result<Mode> {
+        let configs: Configs = self.get_json(self.url(&["configs"]))?;
+        Ok(configs.mode)
+    }
+
+    fn set_mode(&self, mode: Mode) -> Result<()> {
+        let request = self
+            .client
+            .patch(self.url(&["configs"]))
+            .json(&Configs { mode });
+        self.send(request)?;
+        Ok(())
+    }
+
+    fn traffic(&self, on_sample: &mut dyn FnMut(Traffic) -> bool) -> Result<()> {
+        let response = self.send(self.client.get(self.url(&["traffic"])))?;
+        for line in BufReader::new(response).lines() {
+            let line = line.map_err(|e| Error::Invalid(e.to_string()))?;
+            let sample = serde_json::from_str(&line)
+                .map_err(|e| Error::Invalid(format!("traffic sample: {e}")))?;
+            if !on_sample(sample) {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use super::{
+    ControllerClient, Mode, Provider, ProxyGroup, ProxyType, Traffic,
+    error::{Error, Result},
+};
+
+#[derive(Debug)]
+struct Data {
+    groups: Vec<ProxyGroup>,
+    providers: Vec<Provider>,
+    mode: Mode,
+}
+
+/// A controller whose state lives in memory.
+///
+/// Latency tests report the latencies it was created with instead of testing
+/// anything. Made read-only, it serves snapshots.
+#[derive(Debug)]
+pub struct MemoryController {
+    data: Mutex<Data>,
+    read_only: bool,
+}
+
+fn not_found(name: &str) -> Error {
+    Error::Api {
+        status: 404,
+        message: format!("{name} not found"),
+    }
+}
+
+fn timeout() -> Error {
+    Error::Api {
+        status: 504,
+        message: "Timeout".to_string(),
+    }
+}
+
+impl MemoryController {
+    pub fn new(mut groups: Vec<ProxyGroup>, mut providers: Vec<Provider>) -> Self {
+        groups.sort_by_key(|g| g.name.to_lowercase());
+        providers.sort_by_key(|p| p.name.to_lowercase());
+        Self {
+            data: Mutex::new(Data {
+                groups,
+                providers,
+                mode: Mode::Rule,
+            }),
+            read_only: false,
+        }
+    }
+
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn with_mode(self, mode: Mode) -> Self {
+        self.data.lock().unwrap().mode = mode;
+        self
+    }
+
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        if self.read_only {
+            Err(Error::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn latency_of(data: &Data, name: &str) -> Option<u64> {
+        data.groups
+            .iter()
+            .flat_map(|g| g.proxies.iter())
+            .chain(data.providers.iter().flat_map(|p| p.proxies.iter()))
+            .find(|p| p.name == name)
+            .and_then(|p| p.latency)
+    }
+}
+
+impl ControllerClient for MemoryController {
+    fn proxy_groups(&self) -> Result<Vec<ProxyGroup>> {
+        Ok(self.data.lock().unwrap().groups.clone())
+    }
+
+    fn proxy_providers(&self) -> Result<Vec<Provider>> {
+        Ok(self.data.lock().unwrap().providers.clone())
+    }
+
+    fn select_proxy(&self, group: &str, proxy: &str) -> Result<()> {
+        self.check_writable()?;
+        let mut data = self.data.lock().unwrap();
+        let selected = data
+            .groups
+            .iter_mut()
+            .find(|g| g.name == group)
+            .ok_or_else(|| not_found(group))?;
+        if !matches!(selected.proxy_type, ProxyType::Selector) {
+            return Err(Error::Api {
+                status: 400,
+                message: "Must be a Selector".to_string(),
+            });
+        }
+        let latency = selected
+            .proxies
+            .iter()
+            .find(|p| p.name == proxy)
+            .ok_or_else(|| not_found(proxy))?
+            .latency;
+        selected.now = Some(proxy.to_string());
+        selected.latency = latency;
+
+        // groups listing this group show its latency too
+        for member in data
+            .groups
+            .iter_mut()
+            .flat_map(|g| g.proxies.iter_mut())
+            .filter(|p| p.name == group)
+        {
+            member.latency = latency;
+        }
+        Ok(())
+    }
+
+    fn latency_test_group(&self, group: &str) -> Result<HashMap<String, u64>> {
+        self.check_writable()?;
+        let data = self.data.lock().unwrap();
+        let group = data
+            .groups
+            .iter()
+            .find(|g| g.name == group)
+            .ok_or_else(|| not_found(group))?;
+        Ok(group
+            .proxies
+            .iter()
+            .filter_map(|p| p.latency.map(|l| (p.name.clone(), l)))
+            .collect())
+    }
+
+    fn latency_test_proxy(&self, proxy: &str) -> Result<u64> {
+        self.check_writable()?;
+        let data = self.data.lock().unwrap();
+        Self::latency_of(&data, proxy).ok_or_else(timeout)
+    }
+
+    fn update_proxy_provider(&self, provider: &str) -> Result<()> {
+        self.check_writable()?;
+        let data = self.data.lock().unwrap();
+        match data.providers.iter().any(|p| p.name == provider) {
+            true => Ok(()),
+            false => Err(not_found(provider)),
+        }
+    }
+
+    fn latency_test_provider(&self, provider: &str) -> Result<()> {
+        self.update_proxy_provider(provider)
+    }
+
+    fn mode(&self) -> Result<Mode> {
+        Ok(self.data.lock().unwrap().mode)
+    }
+
+    fn set_mode(&self, mode: Mode) -> Result<()> {
+        self.check_writable()?;
+        self.data.lock().unwrap().mode = mode;
+        Ok(())
+    }
+
+    /// Nothing goes through memory, so there is a single idle sample.
+    fn traffic(&self, on_sample: &mut dyn FnMut(Traffic) -> bool) -> Result<()> {
+        on_sample(Traffic::default());
+        Ok(())
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+}
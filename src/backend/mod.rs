@@ -1,15 +1,20 @@
-use std::{
-    collections::HashMap,
-    sync::{LazyLock, atomic::AtomicPtr},
-};
+use std::{collections::HashMap, sync::Arc};
 
 use data::{ProxyEntryRaw, ProxyGroupRaw, Root};
 
+mod client;
 mod data;
-pub use data::Provider;
-use reqwest::Url;
+mod error;
+mod http;
+mod memory;
+pub mod snapshot;
+pub use client::ControllerClient;
+pub use data::{Mode, Provider, ProxyType, SubscriptionInfo};
+pub use error::{Error, Result};
+pub use http::{HttpController, Traffic};
+pub use memory::MemoryController;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ProxyGroup {
     pub name: String,
     pub now: Option<String>,
@@ -117,19 +122,17 @@ impl SelectableProxy {
     }
 }
 
-static BASE_URL: LazyLock<Url> = LazyLock::new(|| Url::parse("http://localhost:9090/").unwrap());
-
-fn get_proxy_groups() -> Vec<ProxyGroup> {
-    let url = BASE_URL.join("proxies").unwrap();
-    let response: data::Root = reqwest::blocking::get(url).unwrap().json().unwrap();
+pub const DEFAULT_CONTROLLER_URL: &str = "http://localhost:9090/";
 
+/// The groups of `response`, which fails on a member missing from its proxies.
+pub(crate) fn build_proxy_groups(response: data::Root) -> Result<Vec<ProxyGroup>> {
     let mut raw_proxy_groups = vec![];
     let mut cache: HashMap<String, SelectableProxy> = HashMap::new();
 
     for (name, v) in response.proxies.iter() {
         match v {
             data::ProxyEntryRaw::Group(proxy_group) => {
-                raw_proxy_groups.push(proxy_group);
+                raw_proxy_groups.push((name, proxy_group));
                 cache.insert(
                     name.clone(),
                     SelectableProxy::from_group(proxy_group, &response, &cache),
@@ -143,133 +146,119 @@ fn get_proxy_groups() -> Vec<ProxyGroup> {
 
     let mut groups = raw_proxy_groups
         .into_iter()
-        .map(|group| {
+        .map(|(key, group)| {
             let name = group.name.clone();
             let now = group.now.clone();
             let proxy_type = group.typ;
             let udp = group.udp;
-            let latency = cache.get(&group.name).unwrap().latency;
+            let latency = cache[key].latency;
 
             let proxies = group
                 .all
                 .iter()
-                .map(|name| cache.get(name).unwrap())
-                .cloned()
-                .collect();
+                .map(|member| {
+                    cache.get(member).cloned().ok_or_else(|| {
+                        Error::Invalid(format!(
+                            "group `{name}` has `{member}`, which is not a proxy"
+                        ))
+                    })
+                })
+                .collect::<Result<_>>()?;
 
-            ProxyGroup {
+            Ok(ProxyGroup {
                 name,
                 now,
                 proxy_type,
                 udp,
                 latency,
                 proxies,
-            }
+            })
         })
-        .collect::<Vec<ProxyGroup>>();
-    groups.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        .collect::<Result<Vec<ProxyGroup>>>()?;
+    groups.sort_by_key(|g| g.name.to_lowercase());
 
-    groups
+    Ok(groups)
 }
 
-pub fn select_proxy(group: &str, proxy: &str) {
-    let url = BASE_URL.join(format!("proxies/{group}").as_str()).unwrap();
-    let client = reqwest::blocking::Client::new();
-    let _ = client
-        .put(url)
-        .header("Content-Type", "application/json")
-        .body(format!(r#"{{"name": "{}"}}"#, proxy))
-        .send()
-        .unwrap()
-        .error_for_status()
-        .unwrap();
-}
-
-const DEFAULT_LATENCY_TEXT_URL: &str = "https://www.gstatic.com/generate_204";
-const TIMEOUT: u64 = 5000;
-pub fn latency_test_group(group: &str) {
-    let mut url = BASE_URL
-        .join(format!("group/{group}/delay").as_str())
-        .unwrap();
-
-    url.query_pairs_mut()
-        .append_pair("url", DEFAULT_LATENCY_TEXT_URL)
-        .append_pair("timeout", &TIMEOUT.to_string());
-
-    let _ = reqwest::blocking::get(url)
-        .unwrap()
-        .error_for_status()
-        .unwrap();
-}
-pub fn latency_test_proxy(proxy: &str) {
-    let mut url = BASE_URL
-        .join(format!("proxies/{proxy}/delay").as_str())
-        .unwrap();
-    url.query_pairs_mut()
-        .append_pair("url", DEFAULT_LATENCY_TEXT_URL)
-        .append_pair("timeout", &TIMEOUT.to_string());
-
-    let _ = reqwest::blocking::get(url)
-        .unwrap()
-        .error_for_status()
-        .unwrap();
-}
-
-fn get_proxy_providers() -> Vec<data::Provider> {
-    let url = BASE_URL.join("providers/proxies").unwrap();
-    let response: data::ProviderRoot = reqwest::blocking::get(url).unwrap().json().unwrap();
-
+pub(crate) fn build_proxy_providers(response: data::ProviderRoot) -> Vec<Provider> {
     let mut providers = response.providers.into_values().collect::<Vec<_>>();
-    providers.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    providers.sort_by_key(|p| p.name.to_lowercase());
 
     providers
 }
 
-pub fn update_proxy_provider(provider: &str) {
-    let url = BASE_URL
-        .join(format!("providers/proxies/{provider}").as_str())
-        .unwrap();
-    let client = reqwest::blocking::Client::new();
-    let _ = client.put(url).send().unwrap().error_for_status().unwrap();
+/// A group having a proxy among its members.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Membership {
+    pub group: String,
+    /// Whether the proxy is the `now` of the group.
+    pub active: bool,
+}
+
+/// The groups of each proxy, in the order of `groups`.
+pub fn build_used_by(groups: &[ProxyGroup]) -> HashMap<String, Vec<Membership>> {
+    let mut used_by: HashMap<String, Vec<Membership>> = HashMap::new();
+    for group in groups {
+        for proxy in &group.proxies {
+            used_by
+                .entry(proxy.name.clone())
+                .or_default()
+                .push(Membership {
+                    group: group.name.clone(),
+                    active: group.now.as_ref() == Some(&proxy.name),
+                });
+        }
+    }
+    used_by
 }
 
-pub fn latency_test_provider(provider: &str) {
-    let url = BASE_URL
-        .join(format!("providers/proxies/{provider}/healthcheck").as_str())
-        .unwrap();
-    let client = reqwest::blocking::Client::new();
-    let _ = client.get(url).send().unwrap().error_for_status().unwrap();
+/// The groups and providers shown by the dashboard, as last fetched from its client.
+#[derive(Debug)]
+pub struct Store {
+    client: Arc<dyn ControllerClient>,
+    groups: Vec<ProxyGroup>,
+    providers: Vec<Provider>,
+    /// The groups of each proxy, see [`build_used_by`].
+    used_by: HashMap<String, Vec<Membership>>,
 }
 
-use std::sync::atomic::Ordering::*;
-static GROUPS_DATA: AtomicPtr<Vec<ProxyGroup>> = AtomicPtr::new(std::ptr::null_mut());
-static PROVIDER_DATA: AtomicPtr<Vec<Provider>> = AtomicPtr::new(std::ptr::null_mut());
+impl Store {
+    pub fn new(client: Arc<dyn ControllerClient>) -> Result<Self> {
+        let mut store = Self {
+            client,
+            groups: vec![],
+            providers: vec![],
+            used_by: HashMap::new(),
+        };
+        store.refresh()?;
+        Ok(store)
+    }
 
-pub fn refresh_data() {
-    let groups = get_proxy_groups();
-    let boxed = Box::new(groups);
-    let ptr = Box::into_raw(boxed);
-    GROUPS_DATA.store(ptr, Relaxed);
+    pub fn client(&self) -> &dyn ControllerClient {
+        self.client.as_ref()
+    }
 
-    let providers = get_proxy_providers();
-    let boxed = Box::new(providers);
-    let ptr = Box::into_raw(boxed);
-    PROVIDER_DATA.store(ptr, Relaxed);
-}
+    /// The client, for work on other threads.
+    pub fn shared_client(&self) -> Arc<dyn ControllerClient> {
+        self.client.clone()
+    }
 
-pub fn get_groups_data() -> &'static Vec<ProxyGroup> {
-    let ptr = GROUPS_DATA.load(Relaxed);
-    if ptr.is_null() {
-        refresh_data();
+    pub fn groups(&self) -> &[ProxyGroup] {
+        &self.groups
     }
 
-    unsafe { &*GROUPS_DATA.load(Relaxed) }
-}
+    pub fn providers(&self) -> &[Provider] {
+        &self.providers
+    }
+
+    pub fn used_by(&self) -> &HashMap<String, Vec<Membership>> {
+        &self.used_by
+    }
 
-pub fn get_providers_data() -> &'static Vec<Provider> {
-    let ptr = PROVIDER_DATA.load(Relaxed);
-    if ptr.is_null() {
-        refresh_data();
+    pub fn refresh(&mut self) -> Result<()> {
+        self.groups = self.client.proxy_groups()?;
+        self.providers = self.client.proxy_providers()?;
+        self.used_by = build_used_by(&self.groups);
+        Ok(())
     }
-    unsafe { &*PROVIDER_DATA.load(Relaxed) }
 }
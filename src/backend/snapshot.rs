@@ -0,0 +1,74 @@
+//! Offline snapshots of the controller state.
+//!
+//! A snapshot is a directory holding the raw responses of `/proxies`,
+//! `/providers/proxies` and `/configs`, so it can be attached to a bug report
+//! and browsed later with `clashi --snapshot <dir>`.
+
+use std::{fmt, fs, io, path::Path};
+
+use serde::de::DeserializeOwned;
+
+use super::{
+    Error, HttpController, MemoryController, build_proxy_groups, build_proxy_providers,
+    data::{Configs, ProviderRoot, Root},
+};
+
+pub const PROXIES_FILE: &str = "proxies.json";
+pub const PROVIDERS_FILE: &str = "providers.json";
+/// Optional, snapshots taken before it was added do not have it.
+pub const CONFIGS_FILE: &str = "configs.json";
+
+/// Loads the snapshot in `dir` into a read-only controller.
+pub fn open(dir: impl AsRef<Path>) -> io::Result<MemoryController> {
+    let dir = dir.as_ref();
+    let path = dir.join(PROXIES_FILE);
+    let proxies: Root = read_json(&path)?;
+    // a snapshot edited by hand may list members it no longer has
+    let groups = build_proxy_groups(proxies).map_err(|e| match e {
+        Error::Invalid(message) => invalid_data(&path, message),
+        e => e.into(),
+    })?;
+    let providers: ProviderRoot = read_json(&dir.join(PROVIDERS_FILE))?;
+    let mut controller = MemoryController::new(groups, build_proxy_providers(providers));
+
+    let configs = dir.join(CONFIGS_FILE);
+    if configs.exists() {
+        let configs: Configs = read_json(&configs)?;
+        controller = controller.with_mode(configs.mode);
+    }
+    Ok(controller.read_only())
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> io::Result<T> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+    serde_json::from_str(&content).map_err(|e| invalid_data(path, e))
+}
+
+fn invalid_data(path: &Path, message: impl fmt::Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{}: {message}", path.display()),
+    )
+}
+
+/// Writes the current state of `controller` into `dir`.
+///
+/// The responses are stored as returned by the controller (pretty printed),
+/// so nothing the models do not know about is lost.
+pub fn write(controller: &HttpController, dir: impl AsRef<Path>) -> io::Result<()> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+
+    let endpoints: [(&[&str], &str); 3] = [
+        (&["proxies"], PROXIES_FILE),
+        (&["providers", "proxies"], PROVIDERS_FILE),
+        (&["configs"], CONFIGS_FILE),
+    ];
+    for (segments, file) in endpoints {
+        let value = controller.get_raw(segments)?;
+        fs::write(dir.join(file), serde_json::to_string_pretty(&value)?)?;
+    }
+
+    Ok(())
+}
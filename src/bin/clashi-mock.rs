@@ -0,0 +1,67 @@
+//! Serves the mock controller, e.g. as the backend when recording the showcase tapes.
+
+use std::{io, process::ExitCode, str::FromStr, time::Duration};
+
+use clashi::mock::{MockConfig, MockController};
+
+const USAGE: &str = "\
+Usage: clashi-mock [options]
+
+Options:
+    --listen <addr>         address to listen on (default 127.0.0.1:9090)
+    --latency <ms>          delay every response by <ms> milliseconds
+    --failure-rate <rate>   share of requests failing on purpose, between 0 and 1
+    --seed <n>              seed choosing which requests fail";
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("clashi-mock: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> io::Result<()> {
+    let mut listen = "127.0.0.1:9090".to_string();
+    let mut config = MockConfig::default();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--listen" => listen = value(&arg, args.next())?,
+            "--latency" => {
+                config = config.latency(Duration::from_millis(value(&arg, args.next())?))
+            }
+            "--failure-rate" => {
+                let rate: f64 = value(&arg, args.next())?;
+                if !(0.0..=1.0).contains(&rate) {
+                    return Err(invalid(format!("{arg} should be between 0 and 1")));
+                }
+                config = config.failure_rate(rate);
+            }
+            "--seed" => config = config.seed(value(&arg, args.next())?),
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ => return Err(invalid(format!("unexpected argument `{arg}`\n\n{USAGE}"))),
+        }
+    }
+
+    let controller = MockController::bind(listen, config)?;
+    println!("mock controller listening on {}", controller.url());
+    controller.wait();
+    Ok(())
+}
+
+fn value<T: FromStr>(flag: &str, value: Option<String>) -> io::Result<T> {
+    value
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| invalid(format!("{flag} expects a valid value\n\n{USAGE}")))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
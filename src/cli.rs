@@ -0,0 +1,578 @@
+//! Command line arguments and the headless subcommands.
+
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+use clashi::{
+    alert::{AlertKind, AlertThresholds},
+    backend::{
+        self, ControllerClient, HttpController, Mode, ProxyGroup, SelectableProxy, snapshot,
+    },
+};
+use reqwest::Url;
+use serde_json::{Value, json};
+
+use crate::status::StatusOptions;
+
+pub const USAGE: &str = "\
+Usage:
+    clashi [options]                         open the dashboard
+    clashi [options] groups                  list groups
+    clashi [options] proxies <group>         list the proxies of a group
+    clashi [options] select <group> <proxy>  select a proxy in a group
+    clashi [options] test <group|proxy>      run a latency test
+    clashi [options] provider update <name>  update a provider subscription
+    clashi [options] provider healthcheck <name>
+                                             run the healthcheck of a provider
+    clashi [options] mode [rule|global|direct]
+                                             show or change the proxy mode
+    clashi [options] status [status options] print a status line, e.g. for tmux or waybar
+    clashi [options] check                   print the subscriptions close to their expiry
+                                             or quota, failing if there are some
+    clashi [options] snapshot <dir>          save the controller state into <dir>
+
+Options:
+    --controller <url>  controller address (default http://localhost:9090/)
+    --snapshot <dir>    read from a saved snapshot instead (read-only)
+    --json              print machine readable output
+    --config <file>     dashboard config (default ~/.config/clashi/config.json)
+
+Status options:
+    --format <format>   placeholders: {group} {now} {latency} {up} {down}
+                        {group:<name>.now} {group:<name>.latency}
+                        (default \"{group}: {now} {latency}ms ↑{up} ↓{down}\")
+    --group <name>      group of {group} {now} {latency} (default Proxy or GLOBAL)
+    --watch             print a line every interval
+    --interval <secs>   time between two lines with --watch (default 1)
+    --waybar            print waybar json objects";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Dashboard,
+    Help,
+    Snapshot(PathBuf),
+    Groups,
+    Proxies(String),
+    Select(String, String),
+    Test(String),
+    ProviderUpdate(String),
+    ProviderHealthcheck(String),
+    Mode(Option<Mode>),
+    Status,
+    Check,
+}
+
+#[derive(Debug)]
+pub struct Cli {
+    pub controller: Url,
+    pub snapshot: Option<PathBuf>,
+    pub json: bool,
+    pub config: Option<PathBuf>,
+    pub status: StatusOptions,
+    pub command: Command,
+}
+
+fn usage_error(message: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("{message}\n\n{USAGE}"))
+}
+
+impl Cli {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> io::Result<Self> {
+        let mut controller = Url::parse(backend::DEFAULT_CONTROLLER_URL).unwrap();
+        let mut snapshot = None;
+        let mut json = false;
+        let mut config = None;
+        let mut status = StatusOptions::default();
+        // the first status option seen, which is only valid with `status`
+        let mut status_flag = None;
+        let mut positional = vec![];
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--controller" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| usage_error("missing <url> argument"))?;
+                    controller = Url::parse(&value)
+                        .map_err(|e| usage_error(format!("invalid controller url: {e}")))?;
+                }
+                "--snapshot" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| usage_error("missing <dir> argument"))?;
+                    snapshot = Some(PathBuf::from(value));
+                }
+                "--json" => json = true,
+                "--config" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| usage_error("missing <file> argument"))?;
+                    config = Some(PathBuf::from(value));
+                }
+                "--format" | "--group" | "--interval" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| usage_error(format!("missing value for `{arg}`")))?;
+                    match arg.as_str() {
+                        "--format" => status.format = value,
+                        "--group" => status.group = Some(value),
+                        _ => {
+                            let secs: f64 = value
+                                .parse()
+                                .ok()
+                                .filter(|secs| *secs > 0.0)
+                                .ok_or_else(|| {
+                                    usage_error(format!("invalid interval `{value}`"))
+                                })?;
+                            status.interval = Duration::from_secs_f64(secs);
+                        }
+                    }
+                    status_flag.get_or_insert(arg);
+                }
+                "--watch" | "--waybar" => {
+                    if arg == "--watch" {
+                        status.watch = true;
+                    } else {
+                        status.waybar = true;
+                    }
+                    status_flag.get_or_insert(arg);
+                }
+                "-h" | "--help" => positional = vec!["help".to_string()],
+                flag if flag.starts_with("--") => {
+                    return Err(usage_error(format!("unexpected option `{flag}`")));
+                }
+                _ => positional.push(arg),
+            }
+        }
+
+        let positional: Vec<&str> = positional.iter().map(String::as_str).collect();
+        let command = match positional.as_slice() {
+            [] => Command::Dashboard,
+            ["help"] => Command::Help,
+            ["snapshot", dir] => Command::Snapshot(PathBuf::from(dir)),
+            ["groups"] => Command::Groups,
+            ["proxies", group] => Command::Proxies(group.to_string()),
+            ["select", group, proxy] => Command::Select(group.to_string(), proxy.to_string()),
+            ["test", name] => Command::Test(name.to_string()),
+            ["provider", "update", name] => Command::ProviderUpdate(name.to_string()),
+            ["provider", "healthcheck", name] => Command::ProviderHealthcheck(name.to_string()),
+            ["status"] => Command::Status,
+            ["check"] => Command::Check,
+            ["mode"] => Command::Mode(None),
+            ["mode", mode] => Command::Mode(Some(
+                mode.parse()
+                    .map_err(|_| usage_error(format!("unknown mode `{mode}`")))?,
+            )),
+            [command, ..] => {
+                return Err(usage_error(format!("unexpected arguments for `{command}`")));
+            }
+        };
+
+        if let Some(flag) = status_flag
+            && command != Command::Status
+        {
+            return Err(usage_error(format!("`{flag}` only applies to `status`")));
+        }
+
+        Ok(Self {
+            controller,
+            snapshot,
+            json,
+            config,
+            status,
+            command,
+        })
+    }
+
+    pub fn client(&self) -> io::Result<Arc<dyn ControllerClient>> {
+        Ok(match self.snapshot.as_ref() {
+            Some(dir) => Arc::new(snapshot::open(dir)?),
+            None => Arc::new(HttpController::new(self.controller.clone())),
+        })
+    }
+}
+
+fn latency_text(latency: Option<u64>) -> String {
+    latency.map_or("--".to_string(), |l| format!("{l}ms"))
+}
+
+/// Prints `rows` as columns aligned on the widest cell.
+fn write_table(out: &mut impl Write, header: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let header: Vec<String> = header.iter().map(|h| h.to_string()).collect();
+    for row in std::iter::once(&header).chain(rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect();
+        writeln!(out, "{}", line.join("  ").trim_end())?;
+    }
+    Ok(())
+}
+
+fn write_json(out: &mut impl Write, value: &Value) -> io::Result<()> {
+    writeln!(out, "{}", serde_json::to_string_pretty(value)?)
+}
+
+fn proxy_json(proxy: &SelectableProxy) -> Value {
+    json!({
+        "name": proxy.name,
+        "type": proxy.proxy_type.str(),
+        "udp": proxy.udp,
+        "latency": proxy.latency,
+    })
+}
+
+fn find_group<'a>(groups: &'a [ProxyGroup], name: &str) -> io::Result<&'a ProxyGroup> {
+    groups
+        .iter()
+        .find(|g| g.name == name)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no group named `{name}`")))
+}
+
+/// Runs a headless subcommand, writing its output to `out`.
+pub fn execute(
+    command: &Command,
+    client: &dyn ControllerClient,
+    json: bool,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    match command {
+        Command::Dashboard
+        | Command::Help
+        | Command::Snapshot(_)
+        | Command::Status
+        | Command::Check => {
+            unreachable!("not a headless subcommand")
+        }
+        Command::Groups => {
+            let groups = client.proxy_groups()?;
+            if json {
+                let groups: Vec<Value> = groups
+                    .iter()
+                    .map(|g| {
+                        json!({
+                            "name": g.name,
+                            "type": g.proxy_type.str(),
+                            "now": g.now,
+                            "latency": g.latency,
+                            "udp": g.udp,
+                            "proxies": g.proxies.iter().map(|p| &p.name).collect::<Vec<_>>(),
+                        })
+                    })
+                    .collect();
+                return write_json(out, &Value::Array(groups));
+            }
+            let rows: Vec<Vec<String>> = groups
+                .iter()
+                .map(|g| {
+                    vec![
+                        g.name.clone(),
+                        g.proxy_type.str().to_string(),
+                        g.now.clone().unwrap_or_default(),
+                        latency_text(g.latency),
+                        g.proxies.len().to_string(),
+                    ]
+                })
+                .collect();
+            write_table(out, &["NAME", "TYPE", "NOW", "LATENCY", "PROXIES"], &rows)
+        }
+        Command::Proxies(group) => {
+            let groups = client.proxy_groups()?;
+            let group = find_group(&groups, group)?;
+            let is_now = |p: &SelectableProxy| group.now.as_ref() == Some(&p.name);
+            if json {
+                let proxies: Vec<Value> = group
+                    .proxies
+                    .iter()
+                    .map(|p| {
+                        let mut value = proxy_json(p);
+                        value["now"] = is_now(p).into();
+                        value
+                    })
+                    .collect();
+                return write_json(out, &Value::Array(proxies));
+            }
+            let rows: Vec<Vec<String>> = group
+                .proxies
+                .iter()
+                .map(|p| {
+                    vec![
+                        if is_now(p) { "*" } else { "" }.to_string(),
+                        p.name.clone(),
+                        p.proxy_type.str().to_string(),
+                        latency_text(p.latency),
+                        p.udp.to_string(),
+                    ]
+                })
+                .collect();
+            write_table(out, &["", "NAME", "TYPE", "LATENCY", "UDP"], &rows)
+        }
+        Command::Select(group, proxy) => {
+            client.select_proxy(group, proxy)?;
+            if json {
+                return write_json(out, &json!({ "group": group, "now": proxy }));
+            }
+            writeln!(out, "{group}: {proxy}")
+        }
+        Command::Test(name) => {
+            let groups = client.proxy_groups()?;
+            if let Ok(group) = find_group(&groups, name) {
+                let delays = client.latency_test_group(name)?;
+                let results: Vec<(&str, Option<u64>)> = group
+                    .proxies
+                    .iter()
+                    .map(|p| (p.name.as_str(), delays.get(&p.name).copied()))
+                    .collect();
+                if json {
+                    let results: serde_json::Map<String, Value> = results
+                        .iter()
+                        .map(|(name, delay)| (name.to_string(), (*delay).into()))
+                        .collect();
+                    write_json(out, &Value::Object(results))?;
+                } else {
+                    let rows: Vec<Vec<String>> = results
+                        .iter()
+                        .map(|(name, delay)| {
+                            let delay = delay.map_or("failed".to_string(), |d| format!("{d}ms"));
+                            vec![name.to_string(), delay]
+                        })
+                        .collect();
+                    write_table(out, &["NAME", "LATENCY"], &rows)?;
+                }
+                if delays.is_empty() {
+                    return Err(io::Error::other(format!("no proxy of `{name}` answered")));
+                }
+                return Ok(());
+            }
+
+            let delay = client.latency_test_proxy(name)?;
+            if json {
+                return write_json(out, &json!({ "name": name, "latency": delay }));
+            }
+            writeln!(out, "{name}: {delay}ms")
+        }
+        Command::ProviderUpdate(name) | Command::ProviderHealthcheck(name) => {
+            if matches!(command, Command::ProviderUpdate(_)) {
+                client.update_proxy_provider(name)?;
+            } else {
+                client.latency_test_provider(name)?;
+            }
+            let providers = client.proxy_providers()?;
+            let provider = providers.iter().find(|p| &p.name == name).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no provider named `{name}`"),
+                )
+            })?;
+            if json {
+                let proxies: Vec<Value> = provider.proxies.iter().map(proxy_json).collect();
+                return write_json(
+                    out,
+                    &json!({
+                        "name": provider.name,
+                        "updated_at": provider.updated_at,
+                        "proxies": proxies,
+                    }),
+                );
+            }
+            let alive = provider
+                .proxies
+                .iter()
+                .filter(|p| p.latency.is_some_and(|l| l > 0))
+                .count();
+            writeln!(
+                out,
+                "{name}: {} proxies, {alive} alive, updated at {}",
+                provider.proxies.len(),
+                provider.updated_at
+            )
+        }
+        Command::Mode(mode) => {
+            if let Some(mode) = mode {
+                client.set_mode(*mode)?;
+            }
+            let mode = client.mode()?;
+            if json {
+                return write_json(out, &json!({ "mode": mode }));
+            }
+            writeln!(out, "{}", mode.str())
+        }
+    }
+}
+
+/// `clashi check`: prints the alerts of the subscriptions, nothing when there
+/// are none, and fails if there are some so that cron reports them.
+pub fn check(
+    thresholds: &AlertThresholds,
+    client: &dyn ControllerClient,
+    json: bool,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let providers = client.proxy_providers()?;
+    let alerts = thresholds.check(&providers, chrono::Utc::now());
+    if json {
+        let alerts: Vec<Value> = alerts
+            .iter()
+            .map(|alert| {
+                let (kind, value) = match alert.kind {
+                    AlertKind::Expired => ("expired", Value::Null),
+                    AlertKind::Expiring(days) => ("expiring", days.into()),
+                    AlertKind::QuotaExceeded => ("quota_exceeded", Value::Null),
+                    AlertKind::Quota(percent) => ("quota", percent.into()),
+                };
+                json!({
+                    "provider": alert.provider,
+                    "kind": kind,
+                    "value": value,
+                    "message": alert.to_string(),
+                })
+            })
+            .collect();
+        write_json(out, &Value::Array(alerts))?;
+    } else {
+        for alert in &alerts {
+            writeln!(out, "{alert}")?;
+        }
+    }
+    match alerts.len() {
+        0 => Ok(()),
+        1 => Err(io::Error::other("1 subscription alert")),
+        n => Err(io::Error::other(format!("{n} subscription alerts"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clashi::mock::{MockConfig, MockController};
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> io::Result<Cli> {
+        Cli::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    fn run(client: &dyn ControllerClient, args: &[&str]) -> io::Result<String> {
+        let cli = parse(args)?;
+        let mut out = vec![];
+        execute(&cli.command, client, cli.json, &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    fn run_json(client: &dyn ControllerClient, args: &[&str]) -> Value {
+        let mut args = args.to_vec();
+        args.push("--json");
+        serde_json::from_str(&run(client, &args).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse(&[]).unwrap().command, Command::Dashboard);
+        assert_eq!(
+            parse(&["select", "Proxy", "JP 01"]).unwrap().command,
+            Command::Select("Proxy".to_string(), "JP 01".to_string())
+        );
+        assert_eq!(
+            parse(&["mode", "global", "--json"]).unwrap().command,
+            Command::Mode(Some(Mode::Global))
+        );
+        let cli = parse(&["--controller", "http://10.0.0.1:9090/", "groups", "--json"]).unwrap();
+        assert_eq!(cli.controller.as_str(), "http://10.0.0.1:9090/");
+        assert!(cli.json);
+        let cli = parse(&["--config", "clashi.json"]).unwrap();
+        assert_eq!(cli.config, Some(PathBuf::from("clashi.json")));
+
+        assert!(parse(&["mode", "rules"]).is_err());
+        assert!(parse(&["select", "Proxy"]).is_err());
+        assert!(parse(&["groups", "--verbose"]).is_err());
+
+        let cli = parse(&[
+            "status",
+            "--watch",
+            "--interval",
+            "0.5",
+            "--format",
+            "{now}",
+        ])
+        .unwrap();
+        assert_eq!(cli.command, Command::Status);
+        assert!(cli.status.watch);
+        assert_eq!(cli.status.interval, Duration::from_millis(500));
+        assert_eq!(cli.status.format, "{now}");
+        assert!(parse(&["groups", "--watch"]).is_err());
+        assert_eq!(parse(&["check"]).unwrap().command, Command::Check);
+        assert!(parse(&["status", "--interval", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_execute() {
+        let mock = MockController::start(MockConfig::default()).unwrap();
+        let client = HttpController::new(mock.url());
+
+        let groups = run(&client, &["groups"]).unwrap();
+        assert!(groups.starts_with("NAME "));
+        assert!(groups.contains("Streaming  Selector     US 01"));
+
+        assert_eq!(
+            run(&client, &["select", "Proxy", "Fallback"]).unwrap(),
+            "Proxy: Fallback\n"
+        );
+        assert!(run(&client, &["select", "Auto", "HK 02"]).is_err());
+        let proxies = run_json(&client, &["proxies", "Proxy"]);
+        assert_eq!(proxies[1]["name"], "Fallback");
+        assert_eq!(proxies[1]["now"], true);
+
+        assert_eq!(run(&client, &["test", "HK 02"]).unwrap(), "HK 02: 63ms\n");
+        assert!(run(&client, &["test", "SG 01"]).is_err());
+        let test = run_json(&client, &["test", "Fallback"]);
+        assert_eq!(test["JP 01"], 91);
+        assert_eq!(test["SG 01"], Value::Null);
+
+        assert_eq!(run(&client, &["mode", "direct"]).unwrap(), "direct\n");
+        assert_eq!(run_json(&client, &["mode"])["mode"], "direct");
+
+        assert!(run(&client, &["provider", "update", "Subscription A"]).is_ok());
+        assert!(run(&client, &["provider", "update", "Subscription"]).is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let mock = MockController::start(MockConfig::default()).unwrap();
+        let client = HttpController::new(mock.url());
+
+        // Subscription A expires in 20 days with 30% of its quota used
+        let mut out = vec![];
+        check(&AlertThresholds::default(), &client, false, &mut out).unwrap();
+        assert!(out.is_empty());
+
+        let thresholds = AlertThresholds {
+            expiry_days: 30,
+            quota_percent: 25.0,
+        };
+        let mut out = vec![];
+        let error = check(&thresholds, &client, false, &mut out).unwrap_err();
+        assert_eq!(error.to_string(), "2 subscription alerts");
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("Subscription A expires in "));
+        assert!(out.ends_with("\nSubscription A used 30% of its quota\n"));
+
+        let mut out = vec![];
+        assert!(check(&thresholds, &client, true, &mut out).is_err());
+        let alerts: Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(alerts[0]["kind"], "expiring");
+        assert_eq!(alerts[1]["kind"], "quota");
+        assert_eq!(alerts[1]["value"], 30.0);
+    }
+}
@@ -0,0 +1,89 @@
+//! The optional `config.json`, read from `$XDG_CONFIG_HOME/clashi/` or `~/.config/clashi/`.
+
+use std::{
+    collections::HashMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    alert::AlertThresholds,
+    keymap::KeysConfig,
+    tab::{CardSizes, SweepConfig},
+    theme::{LatencyThresholds, Theme, ThemeConfig},
+};
+
+pub const FILE_NAME: &str = "config.json";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Keys replacing the default ones of an action, e.g.
+    /// `{"group_proxies": {"test_group": ["t", "ctrl+r"]}}`.
+    pub keys: KeysConfig,
+    /// A built-in theme or one of `themes`, `dark` by default.
+    pub theme: Option<String>,
+    /// Themes replacing some colors of a built-in one, e.g.
+    /// `{"mine": {"base": "light", "accent": "magenta"}}`.
+    pub themes: HashMap<String, ThemeConfig>,
+    pub latency: LatencyThresholds,
+    /// The height and minimum width of the cards, e.g.
+    /// `{"groups": {"height": 5, "width": 30}}`.
+    pub cards: CardSizes,
+    /// When a subscription is flagged, e.g. `{"expiry_days": 3, "quota_percent": 80}`.
+    pub alerts: AlertThresholds,
+    /// How many proxies a latency sweep tests at a time, e.g. `{"concurrency": 16}`.
+    pub sweep: SweepConfig,
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        let dir = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(dir.join("clashi").join(FILE_NAME))
+    }
+
+    /// The theme to use, ignoring the colors when `no_color` is set.
+    pub fn theme(&self, no_color: bool) -> Result<Theme, String> {
+        let theme = match (no_color, &self.theme) {
+            (true, _) => Theme::no_color(),
+            (false, Some(name)) => Theme::named(name, &self.themes)?,
+            (false, None) => Theme::default(),
+        };
+        Ok(theme.with_thresholds(self.latency))
+    }
+
+    /// Reads `path`, or the file at the default path if there is one.
+    pub fn load(path: Option<&Path>) -> io::Result<Self> {
+        let (path, default) = match path {
+            Some(path) => (path.to_path_buf(), false),
+            None => match Self::default_path() {
+                Some(path) => (path, true),
+                None => return Ok(Self::default()),
+            },
+        };
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if default && e.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self::default());
+            }
+            Err(e) => return Err(io::Error::new(e.kind(), format!("{}: {e}", path.display()))),
+        };
+        let config: Self = serde_json::from_str(&content)
+            .map_err(|e| e.to_string())
+            .and_then(|config: Self| config.cards.validate().map(|()| config))
+            .and_then(|config: Self| config.sweep.validate().map(|()| config))
+            .and_then(|config: Self| config.latency.validate().map(|()| config))
+            .map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {e}", path.display()),
+                )
+            })?;
+        Ok(config)
+    }
+}
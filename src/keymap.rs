@@ -0,0 +1,805 @@
+//! Named actions and the keys bound to them.
+
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use strum::IntoEnumIterator;
+use strum_macros::{EnumIter, IntoStaticStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, EnumIter, IntoStaticStr)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Action {
+    Quit,
+    NextTab,
+    PreviousTab,
+    /// Opens the popup searching everything.
+    GoTo,
+    Up,
+    Down,
+    Left,
+    Right,
+    HalfPageDown,
+    HalfPageUp,
+    PageDown,
+    PageUp,
+    First,
+    Last,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    /// Clears the search, or leaves a proxy page.
+    Back,
+    /// Opens the proxy page of a group or provider, or the entry chosen in the palette.
+    Open,
+    /// Opens the subscription of a provider.
+    Details,
+    /// Uses the selected proxy in its group, or in a group chosen for a proxy of a provider.
+    Select,
+    TestGroup,
+    TestProxy,
+    /// Tests the latency of each proxy of the page, showing the results as they come.
+    Sweep,
+    /// Marks a provider for the actions on several providers.
+    Mark,
+    UpdateProvider,
+    HealthcheckProvider,
+    /// Updates the marked providers, or all of them.
+    UpdateAll,
+    /// Runs the healthcheck of the marked providers, or of all of them.
+    HealthcheckAll,
+    Sort,
+    JumpToActive,
+    /// Switches the group page between cards, a table and a tree.
+    View,
+    /// Shows the groups and the proxies of the selected one side by side.
+    Split,
+    /// Lists the keys of the current page.
+    Help,
+}
+
+impl Action {
+    pub fn str(&self) -> &'static str {
+        self.into()
+    }
+
+    /// What the action does, for the key hints and the help.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::NextTab => "next tab",
+            Action::PreviousTab => "previous tab",
+            Action::GoTo => "go to",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::HalfPageDown => "half page down",
+            Action::HalfPageUp => "half page up",
+            Action::PageDown => "page down",
+            Action::PageUp => "page up",
+            Action::First => "first",
+            Action::Last => "last",
+            Action::Search => "search",
+            Action::NextMatch => "next match",
+            Action::PreviousMatch => "previous match",
+            Action::Back => "back",
+            Action::Open => "open",
+            Action::Details => "details",
+            Action::Select => "select",
+            Action::TestGroup => "test group",
+            Action::TestProxy => "test proxy",
+            Action::Sweep => "sweep",
+            Action::Mark => "mark",
+            Action::UpdateProvider => "update",
+            Action::HealthcheckProvider => "healthcheck",
+            Action::UpdateAll => "update all",
+            Action::HealthcheckAll => "healthcheck all",
+            Action::Sort => "sort",
+            Action::JumpToActive => "active proxy",
+            Action::View => "switch view",
+            Action::Split => "split",
+            Action::Help => "help",
+        }
+    }
+}
+
+/// Where a binding applies. Bindings of a page take precedence over global ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, EnumIter, IntoStaticStr)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Context {
+    Global,
+    Groups,
+    GroupProxies,
+    Providers,
+    ProviderDetail,
+    ProviderProxies,
+    /// The popup going to any group, proxy or provider.
+    Palette,
+}
+
+impl Context {
+    pub fn str(&self) -> &'static str {
+        self.into()
+    }
+
+    /// The actions of this context, on top of the global ones for pages.
+    pub fn actions(&self) -> &'static [Action] {
+        use Action::*;
+        match self {
+            Context::Global => &[
+                Quit,
+                NextTab,
+                PreviousTab,
+                GoTo,
+                Up,
+                Down,
+                Left,
+                Right,
+                HalfPageDown,
+                HalfPageUp,
+                PageDown,
+                PageUp,
+                First,
+                Last,
+                Search,
+                NextMatch,
+                PreviousMatch,
+                Back,
+                Help,
+            ],
+            Context::Groups => &[Open, TestGroup, View, Split],
+            Context::GroupProxies => &[
+                Select,
+                TestProxy,
+                TestGroup,
+                Sort,
+                JumpToActive,
+                Split,
+                Sweep,
+            ],
+            Context::Providers => &[
+                Open,
+                Details,
+                UpdateProvider,
+                Mark,
+                UpdateAll,
+                HealthcheckAll,
+            ],
+            Context::ProviderDetail => &[Open, UpdateProvider],
+            Context::ProviderProxies => &[Select, TestProxy, HealthcheckProvider, Sort, Sweep],
+            Context::Palette => &[Up, Down, Open, Back],
+        }
+    }
+
+    /// The actions worth a hint in the bar: the page's own, then the main global ones.
+    pub fn hints(&self) -> Vec<Action> {
+        use Action::*;
+        let global: &[Action] = match self {
+            Context::Global | Context::Palette => &[],
+            Context::Groups | Context::Providers => &[Search, GoTo, NextTab, Help, Quit],
+            Context::GroupProxies | Context::ProviderProxies => {
+                &[Back, Search, GoTo, NextTab, Help, Quit]
+            }
+            Context::ProviderDetail => &[Back, GoTo, NextTab, Help, Quit],
+        };
+        self.actions().iter().chain(global).copied().collect()
+    }
+
+    fn allows(&self, action: Action) -> bool {
+        self.actions().contains(&action)
+            || (!self.is_input() && Context::Global.actions().contains(&action))
+    }
+
+    /// Whether the keys without a binding are typed, as in the query of the palette.
+    /// Such a context has neither the global bindings nor counts.
+    fn is_input(&self) -> bool {
+        matches!(self, Context::Palette)
+    }
+}
+
+/// A key with its modifiers, such as `ctrl+d`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyPress {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl From<KeyEvent> for KeyPress {
+    fn from(key: KeyEvent) -> Self {
+        let mut modifiers = key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+        // shift is part of the character, or of shift+tab
+        if !matches!(key.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers |= key.modifiers & KeyModifiers::SHIFT;
+        }
+        Self {
+            code: key.code,
+            modifiers,
+        }
+    }
+}
+
+impl FromStr for KeyPress {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (prefix, key) = match s.strip_suffix("++") {
+            Some(prefix) => (prefix, "+"),
+            None => match s.rsplit_once('+') {
+                Some((prefix, key)) if !key.is_empty() => (prefix, key),
+                _ => ("", s),
+            },
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in prefix.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier `{modifier}` in `{s}`")),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_lowercase().as_str() {
+                "enter" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "space" => KeyCode::Char(' '),
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key `{key}` in `{s}`")),
+                },
+            },
+        };
+
+        // match what terminals send: `shift+a` is `A`, `shift+tab` is backtab
+        let shift = modifiers.contains(KeyModifiers::SHIFT);
+        let code = match code {
+            KeyCode::Char(c) if shift => KeyCode::Char(c.to_ascii_uppercase()),
+            KeyCode::Tab if shift => KeyCode::BackTab,
+            code => code,
+        };
+        Ok(KeyPress::from(KeyEvent::new(code, modifiers)))
+    }
+}
+
+impl fmt::Display for KeyPress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::BackTab => write!(f, "shift+tab"),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            code => write!(f, "{}", code.to_string().to_lowercase()),
+        }
+    }
+}
+
+/// The keys to press in a row for an action, such as `g g`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeySequence(Vec<KeyPress>);
+
+impl FromStr for KeySequence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let keys = s
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        if keys.is_empty() {
+            return Err("empty key".to_string());
+        }
+        Ok(Self(keys))
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys: Vec<String> = self.0.iter().map(|k| k.to_string()).collect();
+        // `gg` reads better than `g g`
+        let separator = if keys.iter().all(|k| k.chars().count() == 1) {
+            ""
+        } else {
+            " "
+        };
+        write!(f, "{}", keys.join(separator))
+    }
+}
+
+/// The `keys` section of the config: keys by action by context.
+pub type KeysConfig = HashMap<Context, HashMap<Action, Vec<String>>>;
+
+const DEFAULT_KEYS: &[(Context, Action, &[&str])] = &[
+    (Context::Global, Action::Quit, &["q"]),
+    (Context::Global, Action::NextTab, &["tab"]),
+    (Context::Global, Action::PreviousTab, &["shift+tab"]),
+    (Context::Global, Action::GoTo, &["ctrl+p"]),
+    (Context::Global, Action::Up, &["k", "up"]),
+    (Context::Global, Action::Down, &["j", "down"]),
+    (Context::Global, Action::Left, &["h", "left"]),
+    (Context::Global, Action::Right, &["l", "right"]),
+    (Context::Global, Action::HalfPageDown, &["ctrl+d"]),
+    (Context::Global, Action::HalfPageUp, &["ctrl+u"]),
+    (Context::Global, Action::PageDown, &["pagedown", "ctrl+f"]),
+    (Context::Global, Action::PageUp, &["pageup", "ctrl+b"]),
+    (Context::Global, Action::First, &["g g", "home"]),
+    (Context::Global, Action::Last, &["G", "end"]),
+    (Context::Global, Action::Search, &["/"]),
+    (Context::Global, Action::NextMatch, &["n"]),
+    (Context::Global, Action::PreviousMatch, &["N"]),
+    (Context::Global, Action::Back, &["esc"]),
+    (Context::Global, Action::Help, &["?"]),
+    (Context::Groups, Action::Open, &["enter", "space"]),
+    (Context::Groups, Action::TestGroup, &["r"]),
+    (Context::Groups, Action::View, &["v"]),
+    (Context::Groups, Action::Split, &["|"]),
+    (Context::GroupProxies, Action::Select, &["enter", "space"]),
+    (Context::GroupProxies, Action::TestProxy, &["r"]),
+    (Context::GroupProxies, Action::TestGroup, &["R"]),
+    (Context::GroupProxies, Action::Sweep, &["T"]),
+    (Context::GroupProxies, Action::Sort, &["s"]),
+    (Context::GroupProxies, Action::JumpToActive, &["a"]),
+    (Context::GroupProxies, Action::Split, &["|"]),
+    (Context::Providers, Action::Open, &["enter", "space"]),
+    (Context::Providers, Action::Details, &["i"]),
+    (Context::Providers, Action::UpdateProvider, &["f"]),
+    (Context::Providers, Action::Mark, &["m"]),
+    (Context::Providers, Action::UpdateAll, &["F"]),
+    (Context::Providers, Action::HealthcheckAll, &["R"]),
+    (Context::ProviderDetail, Action::Open, &["enter", "space"]),
+    (Context::ProviderDetail, Action::UpdateProvider, &["f"]),
+    (
+        Context::ProviderProxies,
+        Action::Select,
+        &["enter", "space"],
+    ),
+    (Context::ProviderProxies, Action::TestProxy, &["r"]),
+    (
+        Context::ProviderProxies,
+        Action::HealthcheckProvider,
+        &["R"],
+    ),
+    (Context::ProviderProxies, Action::Sweep, &["T"]),
+    (Context::ProviderProxies, Action::Sort, &["s"]),
+    (Context::Palette, Action::Up, &["up", "ctrl+p"]),
+    (Context::Palette, Action::Down, &["down", "ctrl+n"]),
+    (Context::Palette, Action::Open, &["enter"]),
+    (Context::Palette, Action::Back, &["esc"]),
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Resolved {
+    /// `count` is the number typed before the keys, as in `5j`.
+    Action {
+        action: Action,
+        count: Option<usize>,
+    },
+    /// The key started a count or a sequence.
+    Pending,
+    Unbound,
+}
+
+enum Lookup {
+    Exact(Action),
+    Prefix,
+    None,
+}
+
+/// The bindings of every context, and the keys typed so far.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<Context, Vec<(KeySequence, Action)>>,
+    pending: Vec<KeyPress>,
+    count: Option<usize>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut bindings: HashMap<Context, Vec<(KeySequence, Action)>> = HashMap::new();
+        for (context, action, keys) in DEFAULT_KEYS {
+            bindings.entry(*context).or_default().extend(
+                keys.iter()
+                    .map(|k| (k.parse().expect("default keys are valid"), *action)),
+            );
+        }
+        Self {
+            bindings,
+            pending: vec![],
+            count: None,
+        }
+    }
+}
+
+impl Keymap {
+    /// The default keymap, with the actions of `config` bound to their keys instead.
+    pub fn from_config(config: &KeysConfig) -> Result<Self, String> {
+        let mut keymap = Self::default();
+        for (context, actions) in config {
+            let bindings = keymap.bindings.entry(*context).or_default();
+            for (action, keys) in actions {
+                if !context.allows(*action) {
+                    return Err(format!(
+                        "`{}` can not be bound in `{}`",
+                        action.str(),
+                        context.str()
+                    ));
+                }
+                bindings.retain(|(_, a)| a != action);
+                for key in keys {
+                    let sequence = key
+                        .parse()
+                        .map_err(|e| format!("invalid key for `{}`: {e}", action.str()))?;
+                    bindings.push((sequence, *action));
+                }
+            }
+        }
+        Ok(keymap)
+    }
+
+    /// The bindings seen in `context`, its own first.
+    fn visible(&self, context: Context) -> impl Iterator<Item = &(KeySequence, Action)> {
+        let global = match context {
+            Context::Global => None,
+            _ if context.is_input() => None,
+            _ => self.bindings.get(&Context::Global),
+        };
+        self.bindings
+            .get(&context)
+            .into_iter()
+            .chain(global)
+            .flatten()
+    }
+
+    /// The keys of `action` in `context`.
+    pub fn keys(&self, context: Context, action: Action) -> Vec<&KeySequence> {
+        self.visible(context)
+            .filter(|(_, a)| *a == action)
+            .map(|(keys, _)| keys)
+            .collect()
+    }
+
+    fn lookup(&self, context: Context, keys: &[KeyPress]) -> Lookup {
+        if let Some((_, action)) = self.visible(context).find(|(k, _)| k.0 == keys) {
+            return Lookup::Exact(*action);
+        }
+        match self.visible(context).any(|(k, _)| k.0.starts_with(keys)) {
+            true => Lookup::Prefix,
+            false => Lookup::None,
+        }
+    }
+
+    /// Feeds a key typed in `context`.
+    pub fn resolve(&mut self, context: Context, key: KeyEvent) -> Resolved {
+        let press = KeyPress::from(key);
+        if self.pending.is_empty()
+            && press.modifiers.is_empty()
+            && !context.is_input()
+            && let KeyCode::Char(c @ '0'..='9') = press.code
+            && (c != '0' || self.count.is_some())
+            && matches!(self.lookup(context, &[press]), Lookup::None)
+        {
+            let digit = c.to_digit(10).unwrap() as usize;
+            self.count = Some(
+                self.count
+                    .unwrap_or(0)
+                    .saturating_mul(10)
+                    .saturating_add(digit),
+            );
+            return Resolved::Pending;
+        }
+
+        self.pending.push(press);
+        match self.lookup(context, &self.pending) {
+            Lookup::Exact(action) => {
+                self.pending.clear();
+                Resolved::Action {
+                    action,
+                    count: self.count.take(),
+                }
+            }
+            Lookup::Prefix => Resolved::Pending,
+            // an interrupted sequence is dropped, but not the key interrupting it
+            Lookup::None if self.pending.len() > 1 => {
+                self.pending.clear();
+                self.resolve(context, key)
+            }
+            Lookup::None => {
+                self.pending.clear();
+                self.count = None;
+                Resolved::Unbound
+            }
+        }
+    }
+
+    /// Bindings that hide each other: the same keys for two actions of a context,
+    /// or keys starting a longer sequence.
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = vec![];
+        let global = self
+            .bindings
+            .get(&Context::Global)
+            .map_or(&[][..], Vec::as_slice);
+        for context in Context::iter() {
+            let own = self.bindings.get(&context).map_or(&[][..], Vec::as_slice);
+            let inherited = match context {
+                Context::Global => &[][..],
+                _ if context.is_input() => &[][..],
+                _ => global,
+            };
+            for (i, (a, action_a)) in own.iter().enumerate() {
+                // a page binding overrides the global one with the same keys
+                let others = own[i + 1..]
+                    .iter()
+                    .chain(inherited.iter().filter(|(b, _)| b != a));
+                for (b, action_b) in others {
+                    if a == b && action_a != action_b {
+                        conflicts.push(format!(
+                            "`{a}` is bound to both `{}` and `{}` in `{}`",
+                            action_a.str(),
+                            action_b.str(),
+                            context.str()
+                        ));
+                    } else if a != b && (b.0.starts_with(&a.0) || a.0.starts_with(&b.0)) {
+                        let (short, long) = if a.0.len() < b.0.len() {
+                            (a, b)
+                        } else {
+                            (b, a)
+                        };
+                        conflicts.push(format!("`{short}` hides `{long}` in `{}`", context.str()));
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(keymap: &mut Keymap, context: Context, keys: &str) -> Vec<Resolved> {
+        keys.chars()
+            .map(|c| keymap.resolve(context, KeyEvent::from(KeyCode::Char(c))))
+            .collect()
+    }
+
+    fn action(action: Action, count: Option<usize>) -> Resolved {
+        Resolved::Action { action, count }
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        let key = |s: &str| s.parse::<KeyPress>().unwrap();
+        assert_eq!(
+            key("ctrl+d"),
+            KeyPress::from(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(key("shift+a"), key("A"));
+        assert_eq!(key("shift+tab"), key("backtab"));
+        assert_eq!(key("+"), KeyPress::from(KeyEvent::from(KeyCode::Char('+'))));
+        assert_eq!(
+            key("alt++"),
+            KeyPress::from(KeyEvent::new(KeyCode::Char('+'), KeyModifiers::ALT))
+        );
+        assert_eq!(key("F5"), KeyPress::from(KeyEvent::from(KeyCode::F(5))));
+        assert!("hyper+a".parse::<KeyPress>().is_err());
+        assert!("enterr".parse::<KeyPress>().is_err());
+        assert!("".parse::<KeySequence>().is_err());
+
+        // terminals send shifted characters with the shift modifier
+        assert_eq!(
+            KeyPress::from(KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT)),
+            key("G")
+        );
+        for s in ["ctrl+d", "g enter", "space", "shift+tab", "alt+enter", "f5"] {
+            let sequence: KeySequence = s.parse().unwrap();
+            assert_eq!(sequence.to_string().parse::<KeySequence>(), Ok(sequence));
+        }
+        assert_eq!("g g".parse::<KeySequence>().unwrap().to_string(), "gg");
+    }
+
+    #[test]
+    fn test_resolve_by_context() {
+        let mut keymap = Keymap::default();
+        assert_eq!(
+            press(&mut keymap, Context::Groups, "r"),
+            [action(Action::TestGroup, None)]
+        );
+        assert_eq!(
+            press(&mut keymap, Context::GroupProxies, "rR"),
+            [
+                action(Action::TestProxy, None),
+                action(Action::TestGroup, None)
+            ]
+        );
+        assert_eq!(
+            press(&mut keymap, Context::ProviderProxies, "R"),
+            [action(Action::HealthcheckProvider, None)]
+        );
+        assert_eq!(
+            press(&mut keymap, Context::Providers, "r"),
+            [Resolved::Unbound]
+        );
+        assert_eq!(
+            keymap.resolve(
+                Context::Groups,
+                KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL)
+            ),
+            action(Action::HalfPageDown, None)
+        );
+    }
+
+    #[test]
+    fn test_counts_and_sequences() {
+        let mut keymap = Keymap::default();
+        let context = Context::GroupProxies;
+
+        assert_eq!(
+            press(&mut keymap, context, "12j"),
+            [
+                Resolved::Pending,
+                Resolved::Pending,
+                action(Action::Down, Some(12))
+            ]
+        );
+        assert_eq!(
+            press(&mut keymap, context, "gg"),
+            [Resolved::Pending, action(Action::First, None)]
+        );
+        assert_eq!(
+            press(&mut keymap, context, "7gg")[2],
+            action(Action::First, Some(7))
+        );
+        // `0` is no count on its own, and other keys drop the count
+        assert_eq!(press(&mut keymap, context, "0"), [Resolved::Unbound]);
+        assert_eq!(
+            press(&mut keymap, context, "5xj")[1..],
+            [Resolved::Unbound, action(Action::Down, None)]
+        );
+        // an interrupted `gg` is dropped
+        assert_eq!(
+            press(&mut keymap, context, "gjg"),
+            [
+                Resolved::Pending,
+                action(Action::Down, None),
+                Resolved::Pending
+            ]
+        );
+    }
+
+    #[test]
+    fn test_palette_types_unbound_keys() {
+        let mut keymap = Keymap::default();
+        let context = Context::Palette;
+
+        // neither global keys nor counts, so they go to the query
+        assert_eq!(
+            press(&mut keymap, context, "j5g"),
+            [Resolved::Unbound, Resolved::Unbound, Resolved::Unbound]
+        );
+        assert_eq!(
+            keymap.resolve(
+                context,
+                KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL)
+            ),
+            action(Action::Up, None)
+        );
+        assert_eq!(
+            keymap.resolve(context, KeyEvent::from(KeyCode::Esc)),
+            action(Action::Back, None)
+        );
+
+        let invalid = |json| Keymap::from_config(&serde_json::from_str(json).unwrap());
+        assert!(invalid(r#"{ "palette": { "quit": ["ctrl+c"] } }"#).is_err());
+        assert!(invalid(r#"{ "palette": { "down": ["tab"] } }"#).is_ok());
+    }
+
+    #[test]
+    fn test_config_overrides_defaults() {
+        let config: KeysConfig = serde_json::from_str(
+            r#"{
+                "group_proxies": { "test_group": ["t", "ctrl+r"], "sort": [] },
+                "global": { "first": ["home"], "quit": ["ctrl+c", "Z Z"] }
+            }"#,
+        )
+        .unwrap();
+        let mut keymap = Keymap::from_config(&config).unwrap();
+        let context = Context::GroupProxies;
+
+        assert_eq!(
+            press(&mut keymap, context, "tRs"),
+            [
+                action(Action::TestGroup, None),
+                Resolved::Unbound,
+                Resolved::Unbound
+            ]
+        );
+        assert_eq!(press(&mut keymap, context, "g"), [Resolved::Unbound]);
+        assert_eq!(
+            press(&mut keymap, context, "ZZ"),
+            [Resolved::Pending, action(Action::Quit, None)]
+        );
+        // other contexts keep their keys
+        assert_eq!(
+            press(&mut keymap, Context::Groups, "r"),
+            [action(Action::TestGroup, None)]
+        );
+        let keys: Vec<String> = keymap
+            .keys(context, Action::TestGroup)
+            .iter()
+            .map(|k| k.to_string())
+            .collect();
+        assert_eq!(keys, ["t", "ctrl+r"]);
+
+        let invalid = |json| Keymap::from_config(&serde_json::from_str(json).unwrap());
+        assert!(invalid(r#"{ "groups": { "sort": ["s"] } }"#).is_err());
+        assert!(invalid(r#"{ "groups": { "open": ["ctrl+enterr"] } }"#).is_err());
+    }
+
+    #[test]
+    fn test_conflicts() {
+        assert_eq!(Keymap::default().conflicts(), Vec::<String>::new());
+
+        let config: KeysConfig = serde_json::from_str(
+            r#"{
+                "global": { "quit": ["g"] },
+                "groups": { "test_group": ["enter"] }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Keymap::from_config(&config).unwrap().conflicts(),
+            [
+                "`g` hides `gg` in `global`",
+                "`enter` is bound to both `open` and `test_group` in `groups`",
+            ]
+        );
+
+        // a page binding may override a global key, but not hide a global sequence
+        let config: KeysConfig = serde_json::from_str(
+            r#"{
+                "group_proxies": { "sort": ["q"] },
+                "groups": { "view": ["g"] }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Keymap::from_config(&config).unwrap().conflicts(),
+            ["`g` hides `gg` in `groups`"]
+        );
+    }
+}
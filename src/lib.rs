@@ -0,0 +1,9 @@
+pub mod alert;
+pub mod backend;
+pub mod config;
+pub mod keymap;
+pub mod mock;
+pub mod state;
+pub mod tab;
+pub mod theme;
+pub mod usage;
@@ -1,22 +1,33 @@
-use std::{io, time::Instant};
+use std::{io, path::PathBuf, process::ExitCode, time::Duration};
 
-use crossterm::event::{self, Event, KeyEventKind};
+use clashi::{
+    backend::{self, HttpController, Store},
+    config::Config,
+    keymap::Keymap,
+    state::State,
+    tab::BoardWidget,
+};
+use cli::{Cli, Command, USAGE};
+use crossterm::event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyEventKind};
 use ratatui::{
     DefaultTerminal, Frame,
     buffer::Buffer,
     layout::{Layout, Rect},
     style::Stylize,
-    text::Text,
+    text::{Line, Span, Text},
     widgets::{Paragraph, Widget},
 };
-use tab::BoardWidget;
 
-mod backend;
-mod tab;
+mod cli;
+mod status;
+
+/// How often the progress of the background work is redrawn.
+const TICK: Duration = Duration::from_millis(100);
 
 #[derive(Debug)]
 pub struct App {
     board_state: BoardWidget,
+    snapshot: Option<PathBuf>,
     exit: bool,
 }
 impl Widget for &mut App {
@@ -38,38 +49,67 @@ impl Widget for &mut App {
     }
 }
 impl App {
-    fn new() -> Self {
+    fn new(board_state: BoardWidget, snapshot: Option<PathBuf>) -> Self {
         Self {
-            board_state: BoardWidget::new(),
+            board_state,
+            snapshot,
             exit: false,
         }
     }
     fn draw_bar(&self, area: Rect, buf: &mut Buffer) {
-        let [keys, time] = Layout::horizontal([
-            ratatui::layout::Constraint::Percentage(100),
+        let theme = self.board_state.theme();
+        // the first alert, stays until the subscription is fine again
+        let alerts = self.board_state.alerts();
+        let alert = match alerts.as_slice() {
+            [] => Line::default(),
+            [alert] => Line::from(format!(" {alert} ")),
+            [alert, rest @ ..] => Line::from(format!(" {alert} (and {} more) ", rest.len())),
+        }
+        .style(
+            ratatui::style::Style::default()
+                .fg(theme.error)
+                .add_modifier(ratatui::style::Modifier::REVERSED),
+        );
+        let [keys, alert_area, time] = Layout::horizontal([
+            ratatui::layout::Constraint::Fill(1),
+            ratatui::layout::Constraint::Length(alert.width() as u16),
             ratatui::layout::Constraint::Length(5),
         ])
         .areas(area);
 
         // keyboard shortcuts
-        Paragraph::new(Text::styled(
-            "No keys for now",
-            ratatui::style::Style::default().fg(ratatui::style::Color::Green),
-        ))
-        .render(keys, buf);
+        let hint = match (self.board_state.message(), self.snapshot.as_ref()) {
+            (Some(message), _) => Text::styled(
+                message.to_string(),
+                ratatui::style::Style::default().fg(theme.error),
+            ),
+            (None, Some(dir)) => {
+                let mut line = Line::from(Span::styled(
+                    format!("snapshot {} (read-only)  ", dir.display()),
+                    ratatui::style::Style::default().fg(theme.accent),
+                ));
+                line.extend(self.board_state.key_hints());
+                Text::from(line)
+            }
+            (None, None) => Text::from(self.board_state.key_hints()),
+        };
+        Paragraph::new(hint).render(keys, buf);
+        alert.render(alert_area, buf);
 
         // current time
         Paragraph::new(Text::styled(
             chrono::Local::now().format("%H:%M").to_string(),
             ratatui::style::Style::default()
-                .fg(ratatui::style::Color::Black)
-                .bg(ratatui::style::Color::Green),
+                .fg(theme.on_accent)
+                .bg(theme.accent)
+                .add_modifier(theme.selection),
         ))
         .render(time, buf);
     }
     /// runs the application's main loop until the user quits
     pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
         while !self.exit {
+            self.board_state.tick();
             terminal.draw(|frame| self.draw(frame))?;
             self.handle_events()?;
         }
@@ -81,30 +121,99 @@ impl App {
     }
 
     fn handle_events(&mut self) -> io::Result<()> {
+        // redraw the progress of the background work without waiting for an event
+        if self.board_state.is_busy() && !event::poll(TICK)? {
+            return Ok(());
+        }
         match event::read()? {
             // it's important to check that the event is a key press event as
             // crossterm also emits key release and repeat events on Windows.
             Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                 self.handle_key_event(key_event)
             }
+            Event::Mouse(mouse_event) => self.board_state.mouse_event(mouse_event),
             _ => {}
         };
         Ok(())
     }
 
     fn handle_key_event(&mut self, key: event::KeyEvent) {
-        if key.code == crossterm::event::KeyCode::Char('q') {
-            self.exit = true;
-            return;
-        }
         self.board_state.key_event(key);
+        self.exit = self.board_state.should_quit();
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("clashi: {e}");
+            ExitCode::FAILURE
+        }
     }
 }
 
-fn main() -> io::Result<()> {
+fn run() -> io::Result<()> {
+    let cli = Cli::parse(std::env::args().skip(1))?;
+    match &cli.command {
+        Command::Help => {
+            println!("{USAGE}");
+            return Ok(());
+        }
+        Command::Snapshot(dir) => {
+            return backend::snapshot::write(&HttpController::new(cli.controller), dir);
+        }
+        Command::Status => return status::run(&cli.status, cli.client()?, &mut io::stdout()),
+        Command::Check => {
+            let config = Config::load(cli.config.as_deref())?;
+            return cli::check(
+                &config.alerts,
+                cli.client()?.as_ref(),
+                cli.json,
+                &mut io::stdout(),
+            );
+        }
+        Command::Dashboard => {}
+        command => {
+            return cli::execute(command, cli.client()?.as_ref(), cli.json, &mut io::stdout());
+        }
+    }
+
+    let config = Config::load(cli.config.as_deref())?;
+    let keymap = Keymap::from_config(&config.keys)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("config: {e}")))?;
+    // https://no-color.org: any non-empty value disables the colors
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let theme = config
+        .theme(no_color)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("config: {e}")))?;
+    // also shown in the dashboard, this is where the whole list stays readable
+    for conflict in keymap.conflicts() {
+        eprintln!("clashi: key conflict: {conflict}");
+    }
+    let state_path = State::default_path();
+    // a broken state file is only a lost preference
+    let state = state_path.as_deref().map_or_else(State::default, |path| {
+        State::load(path).unwrap_or_else(|e| {
+            eprintln!("clashi: state: {e}");
+            State::default()
+        })
+    });
+    let store = Store::new(cli.client()?)?;
+
+    let board = BoardWidget::new(store)
+        .with_keymap(keymap)
+        .with_theme(theme)
+        .with_card_sizes(config.cards)
+        .with_alerts(config.alerts)
+        .with_sweep(config.sweep)
+        .with_state(state, state_path);
+
     let mut terminal = ratatui::init();
-    let app_result = App::new().run(&mut terminal);
+    let app_result = crossterm::execute!(io::stdout(), EnableMouseCapture)
+        .and_then(|()| App::new(board, cli.snapshot).run(&mut terminal));
 
+    let mouse_result = crossterm::execute!(io::stdout(), DisableMouseCapture);
     ratatui::restore();
-    app_result
+    app_result.and(mouse_result)
 }
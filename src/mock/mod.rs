@@ -0,0 +1,354 @@
+//! A mock clash controller serving deterministic data.
+//!
+//! It implements just enough of the RESTful API for clashi: `/proxies`,
+//! `/providers/proxies`, delay tests, selection, provider update and
+//! healthcheck, the mode in `/configs`, `/traffic` and `/connections`. Every
+//! response can be delayed and a share of them can fail on purpose, see
+//! [`MockConfig`].
+//!
+//! It backs the integration tests of the `backend` module and the
+//! `clashi-mock` binary used to record the showcase tapes.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use reqwest::Url;
+use serde_json::Value;
+use state::{Reply, State};
+
+mod state;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockConfig {
+    latency: Duration,
+    failure_rate: f64,
+    seed: u64,
+    traffic_interval: Duration,
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        Self {
+            latency: Duration::ZERO,
+            failure_rate: 0.0,
+            seed: 0x5eed,
+            traffic_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl MockConfig {
+    /// Sets how long every request waits before it is answered.
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Sets the share of requests answered with `500 Internal Server Error`.
+    ///
+    /// Failures are drawn from a generator seeded with [`MockConfig::seed`],
+    /// so the same sequence of requests always fails the same way.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `rate` is **not** between 0 and 1 inclusively.
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn failure_rate(mut self, rate: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&rate),
+            "Failure rate should be between 0 and 1 inclusively."
+        );
+        self.failure_rate = rate;
+        self
+    }
+
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the time between two samples of the `/traffic` stream.
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn traffic_interval(mut self, interval: Duration) -> Self {
+        self.traffic_interval = interval;
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Shared {
+    config: MockConfig,
+    state: Mutex<State>,
+    rng: Mutex<u64>,
+}
+
+impl Shared {
+    /// xorshift64, good enough to pick failures reproducibly.
+    fn should_fail(&self) -> bool {
+        if self.config.failure_rate <= 0.0 {
+            return false;
+        }
+        let mut rng = self.rng.lock().unwrap();
+        *rng ^= *rng << 13;
+        *rng ^= *rng >> 7;
+        *rng ^= *rng << 17;
+        (*rng as f64 / u64::MAX as f64) < self.config.failure_rate
+    }
+}
+
+/// A running mock controller, stopped when dropped.
+#[derive(Debug)]
+pub struct MockController {
+    addr: SocketAddr,
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockController {
+    /// Starts a mock controller on a free local port.
+    pub fn start(config: MockConfig) -> io::Result<Self> {
+        Self::bind("127.0.0.1:0", config)
+    }
+
+    pub fn bind(addr: impl ToSocketAddrs, config: MockConfig) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let running = Arc::new(AtomicBool::new(true));
+        let shared = Arc::new(Shared {
+            // xorshift gets stuck on 0
+            rng: Mutex::new(config.seed.max(1)),
+            config,
+            state: Mutex::new(State::default()),
+        });
+
+        let handle = thread::spawn({
+            let running = running.clone();
+            move || {
+                for stream in listener.incoming() {
+                    if !running.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let Ok(stream) = stream else { continue };
+                    let shared = shared.clone();
+                    thread::spawn(move || {
+                        let _ = handle_connection(stream, &shared);
+                    });
+                }
+            }
+        });
+
+        Ok(Self {
+            addr,
+            running,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The base url to hand to [`crate::backend::HttpController`].
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://{}/", self.addr)).unwrap()
+    }
+
+    /// Blocks until the server stops, which only happens on failure.
+    pub fn wait(mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for MockController {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        // wake up the accept loop so it can notice
+        let _ = TcpStream::connect(self.addr);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Request {
+    method: String,
+    segments: Vec<String>,
+    body: Vec<u8>,
+}
+
+fn read_request(reader: &mut impl BufRead) -> io::Result<Request> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "bad request line",
+        ));
+    };
+    let method = method.to_string();
+    let path = target.split('?').next().unwrap_or_default();
+    let segments = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(percent_decode)
+        .collect();
+
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().unwrap_or(0);
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    Ok(Request {
+        method,
+        segments,
+        body,
+    })
+}
+
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        500 => "Internal Server Error",
+        504 => "Gateway Timeout",
+        _ => "",
+    }
+}
+
+fn write_reply(stream: &mut TcpStream, (status, body): Reply) -> io::Result<()> {
+    let body = body.map(|b| b.to_string()).unwrap_or_default();
+    write!(
+        stream,
+        "HTTP/1.1 {status} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        status_text(status),
+        body.len(),
+    )?;
+    stream.flush()
+}
+
+fn handle_connection(mut stream: TcpStream, shared: &Shared) -> io::Result<()> {
+    let request = read_request(&mut BufReader::new(&mut stream))?;
+
+    if !shared.config.latency.is_zero() {
+        thread::sleep(shared.config.latency);
+    }
+    if shared.should_fail() {
+        let reply = (
+            500,
+            Some(serde_json::json!({ "message": "injected failure" })),
+        );
+        return write_reply(&mut stream, reply);
+    }
+
+    let segments: Vec<&str> = request.segments.iter().map(String::as_str).collect();
+    if let ("GET", ["traffic"]) = (request.method.as_str(), segments.as_slice()) {
+        return stream_traffic(stream, shared);
+    }
+
+    let reply = route(&request.method, &segments, &request.body, shared);
+    write_reply(&mut stream, reply)?;
+    stream.shutdown(Shutdown::Write)
+}
+
+fn route(method: &str, segments: &[&str], body: &[u8], shared: &Shared) -> Reply {
+    let mut state = shared.state.lock().unwrap();
+    match (method, segments) {
+        ("GET", ["proxies"]) => state.proxies(),
+        ("GET", ["proxies", name]) => state.proxy(name),
+        ("PUT", ["proxies", group]) => state.select(group, body),
+        ("GET", ["proxies", name, "delay"]) => state.proxy_delay(name),
+        ("GET", ["group", name, "delay"]) => state.group_delay(name),
+        ("GET", ["providers", "proxies"]) => state.providers(),
+        ("GET", ["providers", "proxies", name]) => state.provider_get(name),
+        ("PUT", ["providers", "proxies", name]) => state.provider_update(name),
+        ("GET", ["providers", "proxies", name, "healthcheck"]) => state.provider_healthcheck(name),
+        ("GET", ["connections"]) => state.connections(),
+        ("GET", ["configs"]) => state.configs(),
+        ("PATCH", ["configs"]) => state.patch_configs(body),
+        (_, []) => (200, Some(serde_json::json!({ "hello": "clash" }))),
+        _ => (
+            404,
+            Some(serde_json::json!({ "message": "resource not found" })),
+        ),
+    }
+}
+
+/// Writes one traffic sample per interval until the client goes away.
+fn stream_traffic(mut stream: TcpStream, shared: &Shared) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nConnection: close\r\n\r\n"
+    )?;
+    loop {
+        let sample: Value = shared.state.lock().unwrap().traffic();
+        writeln!(stream, "{sample}")?;
+        stream.flush()?;
+        thread::sleep(shared.config.traffic_interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("HK%2001"), "HK 01");
+        assert_eq!(percent_decode("a%2Fb"), "a/b");
+        assert_eq!(percent_decode("%F0%9F%87%AD%F0%9F%87%B0"), "🇭🇰");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+}
@@ -0,0 +1,472 @@
+use chrono::{Duration, SecondsFormat, Utc};
+use serde_json::{Map, Value, json};
+
+/// What an endpoint answers: a status and an optional json body.
+pub(super) type Reply = (u16, Option<Value>);
+
+fn error(status: u16, message: &str) -> Reply {
+    (status, Some(json!({ "message": message })))
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+#[derive(Debug)]
+struct Node {
+    name: &'static str,
+    typ: &'static str,
+    udp: bool,
+    /// The delay every test of this node reports, `None` for a dead node.
+    delay: Option<u64>,
+    history: Vec<(String, u64)>,
+}
+
+#[derive(Debug)]
+struct Group {
+    name: &'static str,
+    typ: &'static str,
+    udp: bool,
+    all: Vec<&'static str>,
+    now: Option<&'static str>,
+}
+
+#[derive(Debug)]
+struct Subscription {
+    upload: u64,
+    download: u64,
+    total: u64,
+    expire: i64,
+}
+
+#[derive(Debug)]
+struct ProviderState {
+    name: &'static str,
+    proxies: Vec<&'static str>,
+    updated_at: String,
+    subscription: Option<Subscription>,
+}
+
+/// The controller state served by the mock.
+///
+/// Everything is derived from a fixed fixture, so the same requests always
+/// yield the same answers (apart from timestamps).
+#[derive(Debug)]
+pub(super) struct State {
+    nodes: Vec<Node>,
+    groups: Vec<Group>,
+    providers: Vec<ProviderState>,
+    mode: &'static str,
+    traffic_tick: u64,
+}
+
+const GIB: u64 = 1024 * 1024 * 1024;
+
+impl Default for State {
+    fn default() -> Self {
+        let node = |name, typ, udp, delay| Node {
+            name,
+            typ,
+            udp,
+            delay,
+            history: match delay {
+                Some(delay) => vec![(now(), delay)],
+                None => vec![],
+            },
+        };
+        let nodes = vec![
+            node("DIRECT", "Direct", true, Some(1)),
+            node("REJECT", "Reject", true, None),
+            node("HK 01", "Shadowsocks", true, Some(48)),
+            node("HK 02", "Shadowsocks", true, Some(63)),
+            node("JP 01", "Vmess", false, Some(91)),
+            node("JP 02", "Vmess", false, Some(840)),
+            node("US 01", "Trojan", true, Some(182)),
+            node("US 02", "Trojan", true, Some(2250)),
+            node("SG 01", "Hysteria2", true, None),
+        ];
+
+        let group = |name, typ, all: &[&'static str], now| Group {
+            name,
+            typ,
+            udp: true,
+            all: all.to_vec(),
+            now,
+        };
+        let groups = vec![
+            group(
+                "Proxy",
+                "Selector",
+                &[
+                    "Auto", "Fallback", "HK 01", "HK 02", "JP 01", "US 01", "DIRECT",
+                ],
+                Some("Auto"),
+            ),
+            group(
+                "Auto",
+                "URLTest",
+                &[
+                    "HK 01", "HK 02", "JP 01", "JP 02", "US 01", "US 02", "SG 01",
+                ],
+                Some("HK 01"),
+            ),
+            group(
+                "Fallback",
+                "Fallback",
+                &["SG 01", "JP 01", "US 01"],
+                Some("JP 01"),
+            ),
+            group(
+                "Streaming",
+                "Selector",
+                &["Proxy", "JP 01", "JP 02", "US 01", "US 02"],
+                Some("US 01"),
+            ),
+            group("Balance", "LoadBalance", &["HK 01", "HK 02"], Some("HK 01")),
+            group(
+                "GLOBAL",
+                "Selector",
+                &[
+                    "DIRECT",
+                    "REJECT",
+                    "Proxy",
+                    "Auto",
+                    "Fallback",
+                    "Streaming",
+                    "Balance",
+                ],
+                Some("DIRECT"),
+            ),
+        ];
+
+        let two_hours_ago =
+            (Utc::now() - Duration::hours(2)).to_rfc3339_opts(SecondsFormat::Millis, true);
+        let providers = vec![
+            ProviderState {
+                name: "Subscription A",
+                proxies: vec!["HK 01", "HK 02", "JP 01", "JP 02"],
+                updated_at: two_hours_ago.clone(),
+                subscription: Some(Subscription {
+                    upload: 3 * GIB,
+                    download: 57 * GIB,
+                    total: 200 * GIB,
+                    expire: (Utc::now() + Duration::days(20)).timestamp(),
+                }),
+            },
+            ProviderState {
+                name: "Subscription B",
+                proxies: vec!["US 01", "US 02", "SG 01"],
+                updated_at: two_hours_ago,
+                subscription: None,
+            },
+        ];
+
+        Self {
+            nodes,
+            groups,
+            providers,
+            mode: "rule",
+            traffic_tick: 0,
+        }
+    }
+}
+
+impl State {
+    fn node(&self, name: &str) -> Option<&Node> {
+        self.nodes.iter().find(|n| n.name == name)
+    }
+
+    fn group(&self, name: &str) -> Option<&Group> {
+        self.groups.iter().find(|g| g.name == name)
+    }
+
+    fn provider(&self, name: &str) -> Option<&ProviderState> {
+        self.providers.iter().find(|p| p.name == name)
+    }
+
+    fn node_json(&self, node: &Node) -> Value {
+        json!({
+            "id": format!("mock-{}", node.name.to_lowercase().replace(' ', "-")),
+            "name": node.name,
+            "type": node.typ,
+            "udp": node.udp,
+            "alive": node.history.last().is_some_and(|(_, delay)| *delay > 0),
+            "history": history_json(&node.history),
+        })
+    }
+
+    fn group_json(&self, group: &Group) -> Value {
+        json!({
+            "name": group.name,
+            "type": group.typ,
+            "udp": group.udp,
+            "all": group.all,
+            "now": group.now,
+            "history": [],
+        })
+    }
+
+    fn entry_json(&self, name: &str) -> Option<Value> {
+        self.group(name)
+            .map(|g| self.group_json(g))
+            .or_else(|| self.node(name).map(|n| self.node_json(n)))
+    }
+
+    fn provider_json(&self, provider: &ProviderState) -> Value {
+        let mut value = json!({
+            "name": provider.name,
+            "type": "Proxy",
+            "vehicleType": "HTTP",
+            "updatedAt": provider.updated_at,
+            "proxies": provider
+                .proxies
+                .iter()
+                .filter_map(|name| self.node(name))
+                .map(|node| self.node_json(node))
+                .collect::<Vec<_>>(),
+        });
+        if let Some(sub) = provider.subscription.as_ref() {
+            value["subscriptionInfo"] = json!({
+                "Upload": sub.upload,
+                "Download": sub.download,
+                "Total": sub.total,
+                "Expire": sub.expire,
+            });
+        }
+        value
+    }
+
+    pub(super) fn proxies(&self) -> Reply {
+        let proxies: Map<String, Value> = self
+            .nodes
+            .iter()
+            .map(|n| (n.name.to_string(), self.node_json(n)))
+            .chain(
+                self.groups
+                    .iter()
+                    .map(|g| (g.name.to_string(), self.group_json(g))),
+            )
+            .collect();
+        (200, Some(json!({ "proxies": proxies })))
+    }
+
+    pub(super) fn proxy(&self, name: &str) -> Reply {
+        match self.entry_json(name) {
+            Some(value) => (200, Some(value)),
+            None => error(404, "resource not found"),
+        }
+    }
+
+    pub(super) fn select(&mut self, group: &str, body: &[u8]) -> Reply {
+        let Some(name) = serde_json::from_slice::<Value>(body)
+            .ok()
+            .and_then(|v| v["name"].as_str().map(str::to_string))
+        else {
+            return error(400, "body invalid");
+        };
+        let Some(group) = self.groups.iter_mut().find(|g| g.name == group) else {
+            return error(404, "resource not found");
+        };
+        if group.typ != "Selector" {
+            return error(400, "Must be a Selector");
+        }
+        match group.all.iter().find(|n| **n == name) {
+            Some(member) => {
+                group.now = Some(member);
+                (204, None)
+            }
+            None => error(400, "Selector update error: proxy not exist"),
+        }
+    }
+
+    /// Resolves the delay of `name`, following the `now` of groups.
+    fn resolve_delay(&self, name: &str) -> Option<u64> {
+        match self.group(name) {
+            Some(group) => group.now.and_then(|now| self.resolve_delay(now)),
+            None => self.node(name).and_then(|n| n.delay),
+        }
+    }
+
+    fn record(&mut self, name: &str, delay: Option<u64>) {
+        if let Some(node) = self.nodes.iter_mut().find(|n| n.name == name) {
+            node.history.push((now(), delay.unwrap_or(0)));
+            if node.history.len() > 10 {
+                node.history.remove(0);
+            }
+        }
+    }
+
+    pub(super) fn proxy_delay(&mut self, name: &str) -> Reply {
+        if self.entry_json(name).is_none() {
+            return error(404, "resource not found");
+        }
+        let delay = self.resolve_delay(name);
+        self.record(name, delay);
+        match delay {
+            Some(delay) => (200, Some(json!({ "delay": delay }))),
+            None => error(504, "Timeout"),
+        }
+    }
+
+    pub(super) fn group_delay(&mut self, name: &str) -> Reply {
+        let Some(group) = self.group(name) else {
+            return error(404, "resource not found");
+        };
+        let members = group.all.clone();
+
+        let mut delays = Map::new();
+        for member in members {
+            let delay = self.resolve_delay(member);
+            self.record(member, delay);
+            if let Some(delay) = delay {
+                delays.insert(member.to_string(), delay.into());
+            }
+        }
+        (200, Some(Value::Object(delays)))
+    }
+
+    pub(super) fn providers(&self) -> Reply {
+        let mut providers: Map<String, Value> = self
+            .providers
+            .iter()
+            .map(|p| (p.name.to_string(), self.provider_json(p)))
+            .collect();
+        // the controller always lists the builtin provider, which clashi filters out
+        let builtin: Vec<Value> = ["DIRECT", "REJECT"]
+            .iter()
+            .filter_map(|name| self.node(name))
+            .map(|node| self.node_json(node))
+            .collect();
+        providers.insert(
+            "default".to_string(),
+            json!({
+                "name": "default",
+                "type": "Proxy",
+                "vehicleType": "Compatible",
+                "updatedAt": now(),
+                "proxies": builtin,
+            }),
+        );
+        (200, Some(json!({ "providers": providers })))
+    }
+
+    pub(super) fn provider_get(&self, name: &str) -> Reply {
+        match self.provider(name) {
+            Some(provider) => (200, Some(self.provider_json(provider))),
+            None => error(404, "resource not found"),
+        }
+    }
+
+    pub(super) fn provider_update(&mut self, name: &str) -> Reply {
+        let Some(provider) = self.providers.iter_mut().find(|p| p.name == name) else {
+            return error(404, "resource not found");
+        };
+        provider.updated_at = now();
+        // swap the first proxy for the next node the provider does not have,
+        // so that every update adds and removes one
+        let last = provider
+            .proxies
+            .last()
+            .and_then(|last| self.nodes.iter().position(|n| n.name == *last))
+            .unwrap_or(0);
+        let next = self
+            .nodes
+            .iter()
+            .cycle()
+            .skip(last + 1)
+            .take(self.nodes.len())
+            .find(|n| !matches!(n.typ, "Direct" | "Reject") && !provider.proxies.contains(&n.name));
+        if let Some(node) = next {
+            if !provider.proxies.is_empty() {
+                provider.proxies.remove(0);
+            }
+            provider.proxies.push(node.name);
+        }
+        (204, None)
+    }
+
+    pub(super) fn provider_healthcheck(&mut self, name: &str) -> Reply {
+        let Some(provider) = self.provider(name) else {
+            return error(404, "resource not found");
+        };
+        for member in provider.proxies.clone() {
+            let delay = self.resolve_delay(member);
+            self.record(member, delay);
+        }
+        (204, None)
+    }
+
+    pub(super) fn configs(&self) -> Reply {
+        (
+            200,
+            Some(json!({
+                "port": 7890,
+                "socks-port": 7891,
+                "allow-lan": false,
+                "log-level": "info",
+                "mode": self.mode,
+            })),
+        )
+    }
+
+    pub(super) fn patch_configs(&mut self, body: &[u8]) -> Reply {
+        let Ok(patch) = serde_json::from_slice::<Value>(body) else {
+            return error(400, "body invalid");
+        };
+        if let Some(mode) = patch.get("mode") {
+            match mode.as_str().map(str::to_lowercase).as_deref() {
+                Some("rule") => self.mode = "rule",
+                Some("global") => self.mode = "global",
+                Some("direct") => self.mode = "direct",
+                _ => return error(400, "invalid mode"),
+            }
+        }
+        (204, None)
+    }
+
+    /// The next sample of the `/traffic` stream.
+    pub(super) fn traffic(&mut self) -> Value {
+        self.traffic_tick += 1;
+        let tick = self.traffic_tick;
+        json!({ "up": 1024 * (tick % 7 + 1), "down": 8 * 1024 * (tick % 5 + 1) })
+    }
+
+    pub(super) fn connections(&self) -> Reply {
+        let connection = |id: &str, host: &str, chains: &[&str], rule: &str| {
+            json!({
+                "id": id,
+                "metadata": {
+                    "network": "tcp",
+                    "type": "HTTP Connect",
+                    "host": host,
+                    "destinationPort": "443",
+                },
+                "upload": 4096,
+                "download": 65536,
+                "start": now(),
+                "chains": chains,
+                "rule": rule,
+                "rulePayload": "",
+            })
+        };
+        (
+            200,
+            Some(json!({
+                "downloadTotal": 131072,
+                "uploadTotal": 8192,
+                "memory": 0,
+                "connections": [
+                    connection("mock-1", "example.com", &["HK 01", "Auto", "Proxy"], "Match"),
+                    connection("mock-2", "example.org", &["DIRECT"], "Domain"),
+                ],
+            })),
+        )
+    }
+}
+
+fn history_json(history: &[(String, u64)]) -> Value {
+    history
+        .iter()
+        .map(|(time, delay)| json!({ "time": time, "delay": delay }))
+        .collect()
+}
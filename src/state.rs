@@ -0,0 +1,81 @@
+//! What the dashboard remembers across runs, in `$XDG_STATE_HOME/clashi/` or `~/.local/state/clashi/`.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{tab::GroupView, usage::UsageHistory};
+
+pub const FILE_NAME: &str = "state.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    pub group_view: GroupView,
+    /// Whether the group tab shows the proxies next to the groups.
+    pub split: bool,
+    /// The traffic of the subscriptions over time.
+    pub usage: UsageHistory,
+}
+
+impl State {
+    pub fn default_path() -> Option<PathBuf> {
+        let dir = env::var_os("XDG_STATE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("state"))
+            })?;
+        Some(dir.join("clashi").join(FILE_NAME))
+    }
+
+    /// Reads `path`, a missing file being the default state.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(io::Error::new(e.kind(), format!("{}: {e}", path.display()))),
+        };
+        serde_json::from_str(&content).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {e}", path.display()),
+            )
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_and_save() {
+        let dir = env::temp_dir().join(format!("clashi-state-{}", std::process::id()));
+        let path = dir.join("nested").join(FILE_NAME);
+        assert_eq!(State::load(&path).unwrap(), State::default());
+
+        let state = State {
+            group_view: GroupView::Tree,
+            split: true,
+            ..State::default()
+        };
+        state.save(&path).unwrap();
+        assert_eq!(State::load(&path).unwrap(), state);
+
+        fs::write(&path, r#"{ "group_view": "list" }"#).unwrap();
+        assert!(State::load(&path).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
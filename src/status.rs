@@ -0,0 +1,279 @@
+//! `clashi status`: one line about the current node and traffic, for status bars.
+
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use bytesize::ByteSize;
+use clashi::backend::{ControllerClient, ProxyGroup, ProxyType, Traffic};
+use serde_json::json;
+
+pub const DEFAULT_FORMAT: &str = "{group}: {now} {latency}ms ↑{up} ↓{down}";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusOptions {
+    pub format: String,
+    /// The group `{now}` and `{latency}` refer to, the main one when `None`.
+    pub group: Option<String>,
+    pub watch: bool,
+    pub interval: Duration,
+    pub waybar: bool,
+}
+
+impl Default for StatusOptions {
+    fn default() -> Self {
+        Self {
+            format: DEFAULT_FORMAT.to_string(),
+            group: None,
+            watch: false,
+            interval: Duration::from_secs(1),
+            waybar: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    Group,
+    Now,
+    Latency,
+    Up,
+    Down,
+    GroupNow(String),
+    GroupLatency(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Field(Field),
+}
+
+/// A parsed format string such as `{group:Proxy.now} {latency}ms ↑{up} ↓{down}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template(Vec<Segment>);
+
+impl Template {
+    pub fn parse(format: &str) -> Result<Self, String> {
+        let mut segments = vec![];
+        let mut rest = format;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                segments.push(Segment::Text(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| format!("unclosed `{{` in `{format}`"))?;
+            let name = &rest[start + 1..start + end];
+            let field = match name {
+                "group" => Field::Group,
+                "now" => Field::Now,
+                "latency" => Field::Latency,
+                "up" => Field::Up,
+                "down" => Field::Down,
+                _ => match name
+                    .strip_prefix("group:")
+                    .and_then(|name| name.rsplit_once('.'))
+                {
+                    Some((group, "now")) => Field::GroupNow(group.to_string()),
+                    Some((group, "latency")) => Field::GroupLatency(group.to_string()),
+                    _ => return Err(format!("unknown placeholder `{{{name}}}`")),
+                },
+            };
+            segments.push(Segment::Field(field));
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+        Ok(Self(segments))
+    }
+
+    fn render(&self, groups: &[ProxyGroup], main: Option<&ProxyGroup>, traffic: Traffic) -> String {
+        let find = |name: &str| groups.iter().find(|g| g.name == name);
+        let now = |group: Option<&ProxyGroup>| {
+            group
+                .and_then(|g| g.now.clone())
+                .unwrap_or_else(|| "--".to_string())
+        };
+        let latency = |group: Option<&ProxyGroup>| {
+            group
+                .and_then(|g| g.latency)
+                .map_or("--".to_string(), |l| l.to_string())
+        };
+        let rate = |bytes: u64| format!("{}/s", ByteSize::b(bytes).display().iec_short());
+
+        self.0
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.clone(),
+                Segment::Field(Field::Group) => main.map(|g| g.name.clone()).unwrap_or_default(),
+                Segment::Field(Field::Now) => now(main),
+                Segment::Field(Field::Latency) => latency(main),
+                Segment::Field(Field::Up) => rate(traffic.up),
+                Segment::Field(Field::Down) => rate(traffic.down),
+                Segment::Field(Field::GroupNow(name)) => now(find(name)),
+                Segment::Field(Field::GroupLatency(name)) => latency(find(name)),
+            })
+            .collect()
+    }
+}
+
+/// The group named `name`, or by default `Proxy` if there is one, `GLOBAL` otherwise.
+fn main_group<'a>(groups: &'a [ProxyGroup], name: Option<&str>) -> Option<&'a ProxyGroup> {
+    match name {
+        Some(name) => groups.iter().find(|g| g.name == name),
+        None => groups
+            .iter()
+            .find(|g| g.name == "Proxy")
+            .or_else(|| groups.iter().find(|g| g.name == "GLOBAL")),
+    }
+}
+
+fn status_line(
+    template: &Template,
+    options: &StatusOptions,
+    groups: &[ProxyGroup],
+    traffic: Traffic,
+) -> String {
+    let main = main_group(groups, options.group.as_deref());
+    let text = template.render(groups, main, traffic);
+    if !options.waybar {
+        return text;
+    }
+
+    let tooltip: Vec<String> = groups
+        .iter()
+        .filter(|g| matches!(g.proxy_type, ProxyType::Selector))
+        .map(|g| {
+            format!(
+                "{}: {} ({})",
+                g.name,
+                g.now.as_deref().unwrap_or("--"),
+                g.latency.map_or("--".to_string(), |l| format!("{l}ms"))
+            )
+        })
+        .collect();
+    let class = match main.and_then(|g| g.latency) {
+        Some(latency) if latency > 0 => "connected",
+        _ => "disconnected",
+    };
+    json!({ "text": text, "tooltip": tooltip.join("\n"), "class": class }).to_string()
+}
+
+fn error_line(options: &StatusOptions, error: &dyn std::fmt::Display) -> String {
+    if options.waybar {
+        json!({ "text": "offline", "tooltip": error.to_string(), "class": "error" }).to_string()
+    } else {
+        format!("offline: {error}")
+    }
+}
+
+pub fn run(
+    options: &StatusOptions,
+    client: Arc<dyn ControllerClient>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let template = Template::parse(&options.format)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    if !options.watch {
+        let groups = client.proxy_groups()?;
+        let mut traffic = Traffic::default();
+        client.traffic(&mut |sample| {
+            traffic = sample;
+            false
+        })?;
+        return writeln!(out, "{}", status_line(&template, options, &groups, traffic));
+    }
+
+    // keep a single traffic stream open instead of reconnecting on every line
+    let latest = Arc::new(Mutex::new(Traffic::default()));
+    thread::spawn({
+        let client = client.clone();
+        let latest = latest.clone();
+        move || {
+            loop {
+                let _ = client.traffic(&mut |sample| {
+                    *latest.lock().unwrap() = sample;
+                    true
+                });
+                // the stream ended, e.g. the controller restarted
+                thread::sleep(Duration::from_secs(1));
+            }
+        }
+    });
+
+    loop {
+        let line = match client.proxy_groups() {
+            Ok(groups) => {
+                let traffic = *latest.lock().unwrap();
+                status_line(&template, options, &groups, traffic)
+            }
+            Err(e) => error_line(options, &e),
+        };
+        writeln!(out, "{line}")?;
+        out.flush()?;
+        thread::sleep(options.interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clashi::backend::HttpController;
+    use clashi::mock::{MockConfig, MockController};
+
+    use super::*;
+
+    #[test]
+    fn test_template_parse() {
+        assert!(Template::parse("{group:Proxy.now} {latency}ms ↑{up} ↓{down}").is_ok());
+        assert!(Template::parse("plain").is_ok());
+        assert!(Template::parse("{latency").is_err());
+        assert!(Template::parse("{speed}").is_err());
+        assert!(Template::parse("{group:Proxy.type}").is_err());
+    }
+
+    #[test]
+    fn test_status_line() {
+        let mock = MockController::start(MockConfig::default()).unwrap();
+        let groups = HttpController::new(mock.url()).proxy_groups().unwrap();
+        let traffic = Traffic {
+            up: 1024,
+            down: 3 * 1024 * 1024,
+        };
+
+        let options = StatusOptions {
+            format: "{group:Streaming.now} {latency}ms ↑{up} ↓{down}".to_string(),
+            ..Default::default()
+        };
+        let template = Template::parse(&options.format).unwrap();
+        assert_eq!(
+            status_line(&template, &options, &groups, traffic),
+            "US 01 48ms ↑1.0K/s ↓3.0M/s"
+        );
+        // the format of the README
+        let template = Template::parse("{group:Proxy.now} {latency}ms ↑{up} ↓{down}").unwrap();
+        assert_eq!(
+            status_line(&template, &options, &groups, traffic),
+            "Auto 48ms ↑1.0K/s ↓3.0M/s"
+        );
+        // only a missing latency is replaced
+        let template = Template::parse("{group:Missing.latency}").unwrap();
+        assert_eq!(status_line(&template, &options, &groups, traffic), "--");
+
+        let options = StatusOptions {
+            group: Some("Fallback".to_string()),
+            waybar: true,
+            ..Default::default()
+        };
+        let template = Template::parse(&options.format).unwrap();
+        let line: serde_json::Value =
+            serde_json::from_str(&status_line(&template, &options, &groups, traffic)).unwrap();
+        assert_eq!(line["text"], "Fallback: JP 01 91ms ↑1.0K/s ↓3.0M/s");
+        assert_eq!(line["class"], "connected");
+    }
+}
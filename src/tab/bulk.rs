@@ -0,0 +1,266 @@
+//! Updates or healthchecks of several providers at once, on background threads,
+//! and the workers they share with the latency sweeps.
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+};
+
+use crate::backend::ControllerClient;
+
+/// At most this many providers are updated or checked at the same time.
+const PARALLELISM: usize = 4;
+
+/// What a worker of [`spawn`] reports about the name at an index.
+#[derive(Debug)]
+pub enum Event<R> {
+    Started(usize),
+    Finished(usize, R),
+}
+
+/// Runs `job` on each of `names` in their order, `parallelism` of them at a time,
+/// until `cancel` is set or the receiver is dropped.
+pub fn spawn<R, F>(
+    client: Arc<dyn ControllerClient>,
+    names: Vec<String>,
+    parallelism: usize,
+    cancel: Arc<AtomicBool>,
+    job: F,
+) -> Receiver<Event<R>>
+where
+    R: Send + 'static,
+    F: Fn(&dyn ControllerClient, &str) -> R + Send + Sync + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    let names: Arc<[String]> = names.into();
+    let next = Arc::new(AtomicUsize::new(0));
+    let job = Arc::new(job);
+    for _ in 0..parallelism.min(names.len()) {
+        let (sender, names, next, client, cancel, job) = (
+            sender.clone(),
+            names.clone(),
+            next.clone(),
+            client.clone(),
+            cancel.clone(),
+            job.clone(),
+        );
+        thread::spawn(move || {
+            while !cancel.load(Ordering::Relaxed) {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(name) = names.get(i) else {
+                    break;
+                };
+                if sender.send(Event::Started(i)).is_err() {
+                    break;
+                }
+                let result = job(client.as_ref(), name);
+                if sender.send(Event::Finished(i, result)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    receiver
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Update,
+    Healthcheck,
+}
+
+impl Operation {
+    fn run(self, client: &dyn ControllerClient, provider: &str) -> Result<(), String> {
+        match self {
+            Operation::Update => client.update_proxy_provider(provider),
+            Operation::Healthcheck => client.latency_test_provider(provider),
+        }
+        .map_err(|e| e.to_string())
+    }
+
+    pub fn running(self) -> &'static str {
+        match self {
+            Operation::Update => "updating",
+            Operation::Healthcheck => "checking",
+        }
+    }
+
+    pub fn done(self) -> &'static str {
+        match self {
+            Operation::Update => "updated",
+            Operation::Healthcheck => "healthchecked",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Progress {
+    Queued,
+    Running,
+    Done,
+    Failed(String),
+}
+
+impl Progress {
+    fn is_over(&self) -> bool {
+        matches!(self, Progress::Done | Progress::Failed(_))
+    }
+}
+
+/// An operation on several providers, started in their order.
+#[derive(Debug)]
+pub struct Bulk {
+    pub operation: Operation,
+    tasks: Vec<(String, Progress)>,
+    receiver: Receiver<Event<Result<(), String>>>,
+}
+
+impl Bulk {
+    /// Runs `operation` on `providers`, [`PARALLELISM`] of them at a time.
+    pub fn start(
+        operation: Operation,
+        client: Arc<dyn ControllerClient>,
+        providers: Vec<String>,
+    ) -> Self {
+        let receiver = spawn(
+            client,
+            providers.clone(),
+            PARALLELISM,
+            Arc::default(),
+            move |client, name| operation.run(client, name),
+        );
+        Self {
+            operation,
+            tasks: providers
+                .into_iter()
+                .map(|name| (name, Progress::Queued))
+                .collect(),
+            receiver,
+        }
+    }
+
+    /// Takes the progress made since the last call, returning whether there was some.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        for event in self.receiver.try_iter() {
+            let (i, progress) = match event {
+                Event::Started(i) => (i, Progress::Running),
+                Event::Finished(i, Ok(())) => (i, Progress::Done),
+                Event::Finished(i, Err(e)) => (i, Progress::Failed(e)),
+            };
+            self.tasks[i].1 = progress;
+            changed = true;
+        }
+        changed
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.tasks.iter().all(|(_, progress)| progress.is_over())
+    }
+
+    /// The progress of `provider`, if it is part of the operation.
+    pub fn progress(&self, provider: &str) -> Option<&Progress> {
+        self.tasks
+            .iter()
+            .find(|(name, _)| name == provider)
+            .map(|(_, progress)| progress)
+    }
+
+    /// The providers the operation succeeded on.
+    pub fn done(&self) -> impl Iterator<Item = &str> {
+        self.tasks
+            .iter()
+            .filter(|(_, progress)| *progress == Progress::Done)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// How many providers succeeded, and which ones failed.
+    pub fn summary(&self) -> String {
+        let failed: Vec<&str> = self
+            .tasks
+            .iter()
+            .filter(|(_, progress)| matches!(progress, Progress::Failed(_)))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        let total = match self.tasks.len() {
+            1 => "1 provider".to_string(),
+            n => format!("{n} providers"),
+        };
+        match failed.as_slice() {
+            [] => format!("{} {total}", self.operation.done()),
+            failed => format!(
+                "{} {} of {total}, failed: {}",
+                self.operation.done(),
+                self.tasks.len() - failed.len(),
+                failed.join(", ")
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::backend::{MemoryController, Provider};
+
+    fn provider(name: &str) -> Provider {
+        Provider {
+            name: name.to_string(),
+            vehicle_type: "HTTP".to_string(),
+            subscription_info: None,
+            updated_at: String::new(),
+            proxies: vec![],
+        }
+    }
+
+    /// Calls `poll_finished` until it reports the background work finished.
+    pub(in crate::tab) fn wait(mut poll_finished: impl FnMut() -> bool) {
+        let start = Instant::now();
+        while !poll_finished() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn finish(bulk: &mut Bulk) {
+        wait(|| {
+            bulk.poll();
+            bulk.is_finished()
+        });
+    }
+
+    #[test]
+    fn test_bulk() {
+        let names: Vec<String> = (0..6).map(|i| format!("P{i}")).collect();
+        let client = Arc::new(MemoryController::new(
+            vec![],
+            names.iter().map(|name| provider(name)).collect(),
+        ));
+
+        let mut bulk = Bulk::start(Operation::Update, client.clone(), names.clone());
+        assert_eq!(bulk.progress("P5"), Some(&Progress::Queued));
+        assert_eq!(bulk.progress("Q"), None);
+        finish(&mut bulk);
+        assert!(!bulk.poll());
+        assert_eq!(bulk.progress("P5"), Some(&Progress::Done));
+        assert_eq!(bulk.summary(), "updated 6 providers");
+
+        let mut bulk = Bulk::start(
+            Operation::Healthcheck,
+            client,
+            vec!["P0".to_string(), "Q".to_string(), "R".to_string()],
+        );
+        finish(&mut bulk);
+        assert!(matches!(bulk.progress("Q"), Some(Progress::Failed(_))));
+        assert_eq!(
+            bulk.summary(),
+            "healthchecked 1 of 3 providers, failed: Q, R"
+        );
+    }
+}
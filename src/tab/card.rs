@@ -1,11 +1,14 @@
 use ratatui::{
     buffer::Buffer,
-    layout::{Layout, Rect},
-    style::{Style, Stylize},
+    layout::{Layout, Position, Rect},
+    style::Style,
     text::Line,
     widgets::{Paragraph, Widget},
 };
 
+use super::{mouse::Clickable, navigation::Navigable};
+use crate::theme::Theme;
+
 #[derive(Debug)]
 pub struct Card {
     height_of_each: u16,
@@ -21,10 +24,10 @@ impl Card {
     }
 
     fn calculate(&self, rect: Rect, state: &mut CardState) -> (usize, Vec<Rect>, Option<Rect>) {
-        // how many rows can be displayed
-        let rows = rect.height / self.height_of_each;
+        // how many rows can be displayed, a short terminal still showing a cut one
+        let rows = (rect.height / self.height_of_each.max(1)).max(1);
         // how many leaked lines
-        let leaks = rect.height % self.height_of_each;
+        let leaks = rect.height.saturating_sub(rows * self.height_of_each);
 
         let (row_page_leak, cards_area) = if leaks == 0 {
             // no leak
@@ -39,8 +42,12 @@ impl Card {
             (Some(leak_rect), cards_area)
         };
 
-        let cards_in_a_row = (cards_area.width / self.threshold_width) as usize;
+        // a narrow terminal falls back to a single column
+        let cards_in_a_row = (cards_area.width / self.threshold_width.max(1)).max(1) as usize;
         state.set_cards_in_a_row(cards_in_a_row);
+        state.set_rows_in_page(rows);
+        // the selection may have been moved before the page was drawn
+        state.select(state.get_current_item());
 
         // item start index
         let start_offset = state.row_offset * cards_in_a_row;
@@ -62,8 +69,6 @@ impl Card {
         })
         .collect();
 
-        state.set_rows_in_page(rows);
-
         (start_offset, render_rows, row_page_leak)
     }
 
@@ -73,6 +78,7 @@ impl Card {
         area: Rect,
         buf: &mut Buffer,
         item_num: usize,
+        theme: &Theme,
         mut draw_cell: impl FnMut(usize, Rect, &mut Buffer, &mut CardState),
     ) {
         state.set_max_item_num(item_num);
@@ -80,18 +86,20 @@ impl Card {
         let (card_start_offset, cards_rect, row_leak) = self.calculate(area, state);
 
         // if all cards can be displayed, no need to draw scroll hint
-        if card_start_offset + cards_rect.len() < item_num {
-            if let Some(rect) = row_leak {
-                draw_scroll_hint(rect, buf);
-            }
+        if card_start_offset + cards_rect.len() < item_num
+            && let Some(rect) = row_leak
+        {
+            draw_scroll_hint(rect, buf, theme);
         }
 
+        state.drawn.clear();
         for (i, card_area) in cards_rect.into_iter().enumerate() {
             let index = card_start_offset + i;
             if index >= item_num {
                 break;
             }
             draw_cell(index, card_area, buf, state);
+            state.drawn.push((index, card_area));
         }
     }
 }
@@ -104,6 +112,10 @@ pub struct CardState {
     cards_in_a_row: Option<usize>,
     rows_in_page: Option<u16>,
     max_item_num: Option<usize>,
+
+    /// The cards of the last draw with their index, and the scrollbar next to them.
+    drawn: Vec<(usize, Rect)>,
+    scrollbar: Rect,
 }
 
 impl CardState {
@@ -113,70 +125,132 @@ impl CardState {
     fn set_rows_in_page(&mut self, rows: u16) {
         self.rows_in_page = Some(rows);
     }
+    pub fn set_scrollbar(&mut self, area: Rect) {
+        self.scrollbar = area;
+    }
     fn set_max_item_num(&mut self, max_item_num: usize) {
         self.max_item_num = Some(max_item_num);
+        self.current_selection = self.current_selection.min(max_item_num.saturating_sub(1));
     }
 
-    fn get_item_num_range(&self) -> (usize, usize) {
-        let cards_in_a_row = self.cards_in_a_row.unwrap();
-        let rows = self.rows_in_page.unwrap();
-        let start_offset = self.row_offset * cards_in_a_row;
-        let end_offset = start_offset + (rows as usize) * cards_in_a_row;
-        (start_offset, end_offset)
-    }
-
-    fn check_if_within_page(&mut self) {
-        let range = self.get_item_num_range();
-        if self.current_selection >= range.1 {
-            self.row_offset += 1;
-        } else if self.current_selection < range.0 {
-            self.row_offset = self.row_offset.saturating_sub(1);
+    /// Selects the item at `index`, scrolling to its row.
+    pub fn select(&mut self, index: usize) {
+        self.current_selection = index;
+        if let (Some(cards_in_a_row), Some(rows)) = (self.cards_in_a_row, self.rows_in_page)
+            && cards_in_a_row > 0
+            && rows > 0
+        {
+            let rows = rows as usize;
+            let row = index / cards_in_a_row;
+            if row < self.row_offset {
+                self.row_offset = row;
+            } else if row >= self.row_offset + rows {
+                self.row_offset = row + 1 - rows;
+            }
+            // a taller page shows the rows above instead of leaving its bottom empty
+            let total_rows = self.max_item_num.unwrap_or(0).div_ceil(cards_in_a_row);
+            self.row_offset = self.row_offset.min(total_rows.saturating_sub(rows));
         }
     }
 
-    pub fn previous_item(&mut self) {
-        self.current_selection = self.current_selection.saturating_sub(1);
-        self.check_if_within_page();
+    pub fn get_current_item(&self) -> usize {
+        self.current_selection
     }
-
-    pub fn next_item(&mut self) {
-        let max_num = self.max_item_num.unwrap() - 1;
-        let select = self.current_selection + 1;
-        self.current_selection = max_num.min(select);
-        self.check_if_within_page();
+    pub fn get_current_row(&self) -> usize {
+        self.current_selection / self.row_len()
     }
-
-    pub fn next_row(&mut self) {
-        let max_num = self.max_item_num.unwrap() - 1;
-        let select = self.current_selection + self.cards_in_a_row.unwrap();
-        self.current_selection = max_num.min(select);
-        self.check_if_within_page();
+    pub fn get_total_rows_count(&self) -> usize {
+        self.len().div_ceil(self.row_len())
     }
+}
 
-    pub fn previous_row(&mut self) {
-        self.current_selection =
-            0.max(self.current_selection as isize - self.cards_in_a_row.unwrap() as isize) as usize;
-        self.check_if_within_page();
-    }
+impl Navigable for CardState {
+    const GRID: bool = true;
 
-    pub fn get_current_item(&self) -> usize {
+    fn len(&self) -> usize {
+        self.max_item_num.unwrap_or(0)
+    }
+    fn selected(&self) -> usize {
         self.current_selection
     }
-    pub fn get_current_row(&self) -> usize {
-        self.current_selection / self.cards_in_a_row.unwrap()
+    fn select(&mut self, position: usize) {
+        CardState::select(self, position);
     }
-    pub fn get_total_rows_count(&self) -> usize {
-        self.max_item_num
-            .unwrap()
-            .div_ceil(self.cards_in_a_row.unwrap())
+    fn row_len(&self) -> usize {
+        self.cards_in_a_row.unwrap_or(1).max(1)
+    }
+    fn page_len(&self) -> usize {
+        self.rows_in_page.unwrap_or(1) as usize * self.row_len()
+    }
+}
+
+impl Clickable for CardState {
+    fn item_at(&self, position: Position) -> Option<usize> {
+        self.drawn
+            .iter()
+            .find(|(_, area)| area.contains(position))
+            .map(|(index, _)| *index)
+    }
+    fn scrollbar(&self) -> Rect {
+        self.scrollbar
     }
 }
 
-fn draw_scroll_hint(area: Rect, buf: &mut Buffer) {
+fn draw_scroll_hint(area: Rect, buf: &mut Buffer, theme: &Theme) {
     let mut lines: Vec<Line> = vec![Line::default(); area.height as usize];
     lines.last_mut().unwrap().push_span("⌄");
     Paragraph::new(lines)
-        .style(Style::new().on_light_blue())
+        .style(Style::new().bg(theme.scroll_hint))
         .centered()
         .render(area, buf);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Draws `len` cards of 4x20 in `width` x `height`, returning the drawn indices.
+    fn draw(state: &mut CardState, width: u16, height: u16, len: usize) -> Vec<usize> {
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        let mut drawn = vec![];
+        Card::new(4, 20).draw(
+            state,
+            area,
+            &mut buf,
+            len,
+            &Theme::default(),
+            |i, _, _, _| drawn.push(i),
+        );
+        drawn
+    }
+
+    #[test]
+    fn test_narrow_and_short_areas() {
+        let mut state = CardState::default();
+        // narrower than a card: a single column
+        assert_eq!(draw(&mut state, 10, 8, 5), [0, 1]);
+        assert_eq!(state.row_len(), 1);
+        assert_eq!(state.get_total_rows_count(), 5);
+
+        // shorter than a card: a single cut row
+        assert_eq!(draw(&mut state, 60, 2, 5), [0, 1, 2]);
+        assert_eq!(draw(&mut state, 0, 0, 5), [0]);
+        assert!(draw(&mut state, 60, 8, 0).is_empty());
+    }
+
+    #[test]
+    fn test_selection_stays_visible_on_resize() {
+        let mut state = CardState::default();
+        draw(&mut state, 60, 8, 20);
+        state.select(19);
+        assert_eq!(draw(&mut state, 60, 8, 20), [15, 16, 17, 18, 19]);
+
+        // fewer cards in a row: the selection moves down a few rows
+        assert_eq!(draw(&mut state, 40, 8, 20), [16, 17, 18, 19]);
+        assert_eq!(draw(&mut state, 10, 4, 20), [19]);
+
+        // a taller page fills up with the rows above
+        assert_eq!(draw(&mut state, 60, 20, 20), (6..20).collect::<Vec<_>>());
+    }
+}
@@ -8,6 +8,7 @@ use ratatui::{
 };
 
 use super::card::{Card, CardState};
+use crate::theme::Theme;
 
 #[derive(Debug)]
 pub struct CardPage {
@@ -29,6 +30,7 @@ impl CardPage {
         area: Rect,
         buf: &mut Buffer,
         max_len: usize,
+        theme: &Theme,
         cb: impl FnMut(usize, Rect, &mut Buffer, &mut CardState),
     ) {
         let [cards_area, scrollbar_area] = Layout::horizontal([
@@ -38,7 +40,8 @@ impl CardPage {
         .areas(area);
 
         self.card
-            .draw(&mut self.card_state, cards_area, buf, max_len, cb);
+            .draw(&mut self.card_state, cards_area, buf, max_len, theme, cb);
+        self.card_state.set_scrollbar(scrollbar_area);
 
         self.scroll_state = self
             .scroll_state
@@ -48,7 +51,7 @@ impl CardPage {
         StatefulWidget::render(
             Scrollbar::default()
                 .orientation(ScrollbarOrientation::VerticalRight)
-                .thumb_style(Style::default().fg(ratatui::style::Color::Green))
+                .thumb_style(Style::default().fg(theme.accent))
                 .begin_symbol(Some("↑"))
                 .end_symbol(Some("↓")),
             scrollbar_area,
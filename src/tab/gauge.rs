@@ -0,0 +1,394 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Direction, Rect},
+    style::{Color, Style, Styled},
+    text::Line,
+    widgets::Widget,
+};
+
+/// Cells filled from the bottom by eighths.
+const BAR_PROGESS_CHAR: [&str; 9] = [" ", "▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+/// Cells filled from the left by eighths.
+const HORIZONTAL_PROGRESS_CHAR: [&str; 9] = [" ", "▏", "▎", "▍", "▌", "▋", "▊", "▉", "█"];
+
+/// Replaces the last cell of a bar past its limit.
+const OVER_LIMIT_SYMBOL: &str = "!";
+
+/// A cell of a bar: `eighths` of it filled by the segment at `segment`, the
+/// rest by the segment at `next` if there is one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fill {
+    eighths: usize,
+    segment: usize,
+    next: Option<usize>,
+}
+
+/// Fills `len` cells with segments ending at `ends`, counted in cells from the
+/// start of the bar. Returns the symbols and fills in drawing order: from the
+/// top for a vertical bar, which fills up from the bottom.
+fn cal_chars(direction: Direction, len: usize, ends: &[f64]) -> Vec<(&'static str, Option<Fill>)> {
+    let chars = match direction {
+        Direction::Vertical => &BAR_PROGESS_CHAR,
+        Direction::Horizontal => &HORIZONTAL_PROGRESS_CHAR,
+    };
+    let mut cells: Vec<_> = (0..len)
+        .map(|i| {
+            let start = i as f64;
+            let segment = ends.iter().position(|&end| end > start)?;
+            let end = ends[segment];
+            if end >= start + 1.0 {
+                return Some(Fill {
+                    eighths: 8,
+                    segment,
+                    next: None,
+                });
+            }
+            let next = (segment + 1..ends.len()).find(|&next| ends[next] > end);
+            let eighths = ((end - start) * 8.0).round() as usize;
+            (eighths > 0 || next.is_some()).then_some(Fill {
+                eighths,
+                segment,
+                next,
+            })
+        })
+        .map(|fill| (chars[fill.map_or(0, |fill| fill.eighths)], fill))
+        .collect();
+
+    if direction == Direction::Vertical {
+        cells.reverse();
+    }
+    cells
+}
+
+/// A bar filled up to a ratio, or with several segments stacked on each other.
+///
+/// Ratios past 1 are clamped, the segments keeping their proportions, and the
+/// last cell marks the bar as over its limit.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Gauge<'a> {
+    direction: Direction,
+    segments: Vec<(f64, Style)>,
+    style: Style,
+    bar_style: Style,
+    /// Styles of the bar from a total ratio, in increasing order.
+    thresholds: Vec<(f64, Style)>,
+    over_limit_style: Style,
+    label: Option<Line<'a>>,
+}
+
+impl<'a> Gauge<'a> {
+    /// A bar filling up from the bottom.
+    pub fn vertical() -> Self {
+        Self::default()
+    }
+
+    /// A bar filling up from the left.
+    pub fn horizontal() -> Self {
+        Self {
+            direction: Direction::Horizontal,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the bar progression from a ratio (float).
+    ///
+    /// `ratio` is the ratio between filled bar over empty bar (i.e. `3/4` completion is `0.75`).
+    /// This is more easily seen as a floating point percentage (e.g. 42% = `0.42`).
+    ///
+    /// A ratio over 1 fills the bar and marks it as over its limit, a negative one is 0.
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn ratio(mut self, ratio: f64) -> Self {
+        self.segments = vec![(ratio, Style::default())];
+        self
+    }
+
+    /// Stacks a segment of `ratio` on the previous ones, `style` being patched
+    /// over the style of the bar.
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn segment<S: Into<Style>>(mut self, ratio: f64, style: S) -> Self {
+        self.segments.push((ratio, style.into()));
+        self
+    }
+
+    /// Sets the widget style.
+    ///
+    /// `style` accepts any type that is convertible to [`Style`] (e.g. [`Style`], [`Color`], or
+    /// your own type that implements [`Into<Style>`]).
+    ///
+    /// This will style everything except the bar itself, so basically the block (if any) and
+    /// background.
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn style<S: Into<Style>>(mut self, style: S) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    /// Sets the style of the bar.
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn bar_style<S: Into<Style>>(mut self, style: S) -> Self {
+        self.bar_style = style.into();
+        self
+    }
+
+    /// Styles the bar with `style` once the total ratio reaches `ratio`, the
+    /// highest threshold reached winning.
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn threshold<S: Into<Style>>(mut self, ratio: f64, style: S) -> Self {
+        self.thresholds.push((ratio, style.into()));
+        self.thresholds.sort_by(|a, b| a.0.total_cmp(&b.0));
+        self
+    }
+
+    /// Sets the style of the cell marking a bar over its limit.
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn over_limit_style<S: Into<Style>>(mut self, style: S) -> Self {
+        self.over_limit_style = style.into();
+        self
+    }
+
+    /// Writes `label` in the middle of the bar.
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn label<L: Into<Line<'a>>>(mut self, label: L) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// The sum of the ratios of the segments.
+    pub fn total(&self) -> f64 {
+        self.segments
+            .iter()
+            .map(|&(ratio, _)| ratio.max(0.0))
+            .filter(|ratio| ratio.is_finite())
+            .sum()
+    }
+
+    /// The style of the bar, with the highest threshold reached.
+    fn current_bar_style(&self) -> Style {
+        let total = self.total();
+        self.thresholds
+            .iter()
+            .rev()
+            .find(|&&(ratio, _)| total >= ratio)
+            .map_or(self.bar_style, |&(_, style)| self.bar_style.patch(style))
+    }
+}
+
+impl Widget for Gauge<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        buf.set_style(area, self.style);
+        if area.is_empty() {
+            return;
+        }
+
+        let len = match self.direction {
+            Direction::Vertical => area.height,
+            Direction::Horizontal => area.width,
+        } as usize;
+        let total = self.total();
+        let scale = len as f64 / total.max(1.0);
+        let bar_style = self.current_bar_style();
+        let styles: Vec<Style> = self
+            .segments
+            .iter()
+            .map(|&(_, style)| bar_style.patch(style))
+            .collect();
+        let ends: Vec<f64> = self
+            .segments
+            .iter()
+            .scan(0.0, |end, &(ratio, _)| {
+                if ratio.is_finite() {
+                    *end += ratio.max(0.0) * scale;
+                }
+                Some(*end)
+            })
+            .collect();
+
+        let cells = cal_chars(self.direction, len, &ends);
+        let position = |i: usize| match self.direction {
+            Direction::Vertical => (area.x, area.y + i as u16),
+            Direction::Horizontal => (area.x + i as u16, area.y),
+        };
+        // the bar is repeated across its thickness
+        let (across, offset) = match self.direction {
+            Direction::Vertical => (area.width, (1, 0)),
+            Direction::Horizontal => (area.height, (0, 1)),
+        };
+        for (i, &(symbol, fill)) in cells.iter().enumerate() {
+            let Some(fill) = fill else {
+                continue;
+            };
+            let (x, y) = position(i);
+            for j in 0..across {
+                let cell = &mut buf[(x + offset.0 * j, y + offset.1 * j)];
+                cell.set_symbol(symbol);
+                cell.set_style(styles[fill.segment]);
+                if let Some(bg) = fill.next.and_then(|next| styles[next].fg) {
+                    cell.set_bg(bg);
+                }
+            }
+        }
+        if total > 1.0 {
+            let (x, y) = position(match self.direction {
+                Direction::Vertical => 0,
+                Direction::Horizontal => len - 1,
+            });
+            for j in 0..across {
+                buf[(x + offset.0 * j, y + offset.1 * j)]
+                    .set_symbol(OVER_LIMIT_SYMBOL)
+                    .set_style(self.over_limit_style);
+            }
+        }
+
+        if let Some(label) = &self.label {
+            let width = (label.width() as u16).min(area.width);
+            let x = area.x + (area.width - width) / 2;
+            let y = area.y + area.height.saturating_sub(1) / 2;
+            buf.set_line(x, y, label, width);
+            // the label stays readable on the filled part of the bar
+            for x in x..x + width {
+                let cell = &mut buf[(x, y)];
+                let filled = match self.direction {
+                    Direction::Vertical => cells[(y - area.y) as usize].1,
+                    Direction::Horizontal => cells[(x - area.x) as usize].1,
+                };
+                if let Some(fill) = filled.filter(|fill| fill.eighths >= 4)
+                    && let Some(color) = styles[fill.segment].fg
+                {
+                    cell.set_fg(self.style.bg.unwrap_or(Color::Reset))
+                        .set_bg(color);
+                }
+            }
+        }
+    }
+}
+
+impl Styled for Gauge<'_> {
+    type Item = Self;
+
+    fn style(&self) -> Style {
+        self.style
+    }
+
+    fn set_style<S: Into<Style>>(self, style: S) -> Self::Item {
+        self.style(style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+
+    use ratatui::style::Stylize;
+
+    use super::*;
+
+    fn chars(direction: Direction, len: usize, ends: &[f64]) -> Vec<&'static str> {
+        cal_chars(direction, len, ends)
+            .into_iter()
+            .map(|(symbol, _)| symbol)
+            .collect()
+    }
+
+    #[test]
+    fn test_cal_chars() {
+        let vertical = |ratio: f64| chars(Direction::Vertical, 4, &[4.0 * ratio]);
+        assert_vec_eq(vertical(1.0), ["█", "█", "█", "█"].to_vec());
+        assert_vec_eq(vertical(0.75), [" ", "█", "█", "█"].to_vec());
+        assert_vec_eq(vertical(0.5), [" ", " ", "█", "█"].to_vec());
+        assert_vec_eq(vertical(0.25), [" ", " ", " ", "█"].to_vec());
+
+        assert_vec_eq(vertical(0.125), [" ", " ", " ", "▄"].to_vec());
+        assert_vec_eq(vertical(0.0), [" ", " ", " ", " "].to_vec());
+
+        assert_vec_eq(
+            chars(Direction::Horizontal, 4, &[2.5]),
+            ["█", "█", "▌", " "].to_vec(),
+        );
+        assert_vec_eq(chars(Direction::Horizontal, 0, &[2.5]), [].to_vec());
+    }
+
+    #[test]
+    fn test_cal_chars_stacked() {
+        // the second segment starts in the middle of the second cell
+        let cells = cal_chars(Direction::Horizontal, 4, &[1.5, 1.5, 3.0]);
+        assert_vec_eq(
+            cells.iter().map(|&(symbol, _)| symbol).collect(),
+            ["█", "▌", "█", " "].to_vec(),
+        );
+        assert_eq!(
+            cells[1].1,
+            Some(Fill {
+                eighths: 4,
+                segment: 0,
+                next: Some(2)
+            })
+        );
+        assert_eq!(cells[2].1.map(|fill| fill.segment), Some(2));
+        assert_eq!(cells[3].1, None);
+    }
+
+    fn render(gauge: Gauge, width: u16, height: u16) -> Buffer {
+        let mut buf = Buffer::empty(Rect::new(0, 0, width, height));
+        gauge.render(buf.area, &mut buf);
+        buf
+    }
+
+    fn symbols(buf: &Buffer) -> String {
+        buf.content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn test_over_limit_and_thresholds() {
+        // over its limit, a bar no longer panics
+        let buf = render(Gauge::vertical().ratio(1.5), 1, 3);
+        assert_eq!(symbols(&buf), "!██");
+        let buf = render(Gauge::horizontal().ratio(-0.5), 3, 1);
+        assert_eq!(symbols(&buf), "   ");
+
+        let gauge = Gauge::horizontal()
+            .bar_style(Color::Green)
+            .threshold(0.9, Color::Red)
+            .threshold(0.7, Color::Yellow);
+        assert_eq!(
+            render(gauge.clone().ratio(0.5), 4, 1)[(0, 0)].fg,
+            Color::Green
+        );
+        assert_eq!(
+            render(gauge.clone().ratio(0.8), 4, 1)[(0, 0)].fg,
+            Color::Yellow
+        );
+        assert_eq!(render(gauge.ratio(0.9), 4, 1)[(0, 0)].fg, Color::Red);
+    }
+
+    #[test]
+    fn test_stacked_segments_and_label() {
+        // upload and download over the total keep their proportions
+        let buf = render(
+            Gauge::horizontal()
+                .segment(0.3, Color::Cyan)
+                .segment(0.9, Color::Blue)
+                .over_limit_style(Style::new().red()),
+            8,
+            1,
+        );
+        assert_eq!(symbols(&buf), "███████!");
+        assert_eq!((buf[(1, 0)].fg, buf[(2, 0)].fg), (Color::Cyan, Color::Blue));
+        assert_eq!(buf[(7, 0)].fg, Color::Red);
+
+        let buf = render(
+            Gauge::horizontal().segment(0.5, Color::Green).label("50%"),
+            8,
+            1,
+        );
+        assert_eq!(symbols(&buf), "██50%   ");
+        assert_eq!(buf[(2, 0)].bg, Color::Green);
+        assert_eq!(buf[(4, 0)].bg, Color::Reset);
+    }
+
+    fn assert_vec_eq<T: PartialEq + fmt::Debug>(vec1: Vec<T>, vec2: Vec<T>) {
+        assert_eq!(vec1.len(), vec2.len());
+        for i in 0..vec1.len() {
+            assert_eq!(vec1[i], vec2[i]);
+        }
+    }
+}
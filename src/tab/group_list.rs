@@ -0,0 +1,413 @@
+use std::collections::HashMap;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Layout, Position, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Cell, Row, Scrollbar, ScrollbarState, StatefulWidget, Table, TableState},
+};
+
+use super::{mouse::Clickable, navigation::Navigable, search};
+use crate::{backend::ProxyGroup, theme::Theme};
+
+/// A row of the list: a group, drawn after `prefix` in a tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    index: usize,
+    prefix: String,
+}
+
+/// A group with the groups it references.
+#[derive(Debug)]
+struct Node {
+    index: usize,
+    children: Vec<Node>,
+}
+
+/// Nests every group under the groups having it as a proxy, starting from the
+/// groups no other group references. A group referenced twice shows up twice.
+fn tree(groups: &[ProxyGroup]) -> Vec<Node> {
+    let by_name: HashMap<&str, usize> = groups
+        .iter()
+        .enumerate()
+        .map(|(i, g)| (g.name.as_str(), i))
+        .collect();
+    let children: Vec<Vec<usize>> = groups
+        .iter()
+        .map(|g| {
+            g.proxies
+                .iter()
+                .filter(|p| p.proxy_type.is_group())
+                .filter_map(|p| by_name.get(p.name.as_str()).copied())
+                .collect()
+        })
+        .collect();
+
+    fn build(index: usize, children: &[Vec<usize>], ancestors: &mut Vec<usize>) -> Node {
+        ancestors.push(index);
+        let mut nodes = vec![];
+        for &child in &children[index] {
+            // a cycle stops where it would repeat itself
+            if !ancestors.contains(&child) {
+                nodes.push(build(child, children, ancestors));
+            }
+        }
+        ancestors.pop();
+        Node {
+            index,
+            children: nodes,
+        }
+    }
+
+    let referenced: Vec<bool> = (0..groups.len())
+        .map(|i| children.iter().any(|c| c.contains(&i)))
+        .collect();
+    let mut roots: Vec<usize> = (0..groups.len()).filter(|&i| !referenced[i]).collect();
+    // groups only reachable through a cycle become roots as well
+    let mut reached = vec![false; groups.len()];
+    fn reach(index: usize, children: &[Vec<usize>], reached: &mut [bool]) {
+        if !reached[index] {
+            reached[index] = true;
+            for &child in &children[index] {
+                reach(child, children, reached);
+            }
+        }
+    }
+    for &root in &roots {
+        reach(root, &children, &mut reached);
+    }
+    for i in 0..groups.len() {
+        if !reached[i] {
+            roots.push(i);
+            reach(i, &children, &mut reached);
+        }
+    }
+    roots
+        .into_iter()
+        .map(|root| build(root, &children, &mut vec![]))
+        .collect()
+}
+
+/// Keeps the nodes that are shown, or lead to one that is.
+fn prune(nodes: Vec<Node>, shown: &impl Fn(usize) -> bool) -> Vec<Node> {
+    nodes
+        .into_iter()
+        .filter_map(|node| {
+            let children = prune(node.children, shown);
+            (shown(node.index) || !children.is_empty()).then_some(Node {
+                index: node.index,
+                children,
+            })
+        })
+        .collect()
+}
+
+/// Lists `nodes` with the branches leading to them, the roots having none.
+fn flatten(nodes: &[Node], indent: Option<&str>, entries: &mut Vec<Entry>) {
+    for (i, node) in nodes.iter().enumerate() {
+        let last = i + 1 == nodes.len();
+        let (prefix, children_indent) = match indent {
+            None => (String::new(), String::new()),
+            Some(indent) => (
+                format!("{indent}{}", if last { "└─ " } else { "├─ " }),
+                format!("{indent}{}", if last { "   " } else { "│  " }),
+            ),
+        };
+        entries.push(Entry {
+            index: node.index,
+            prefix,
+        });
+        flatten(&node.children, Some(&children_indent), entries);
+    }
+}
+
+/// The group page as a table, or as a tree of the groups referencing each other.
+#[derive(Debug)]
+pub struct GroupList {
+    state: TableState,
+    scroll_state: ScrollbarState,
+    entries: Vec<Entry>,
+    /// The number of rows the table showed when it was last drawn.
+    page_len: usize,
+    /// Where the rows and the scrollbar were last drawn.
+    rows: Rect,
+    scrollbar: Rect,
+}
+
+impl GroupList {
+    pub fn new() -> Self {
+        Self {
+            state: TableState::new().with_selected(0),
+            scroll_state: ScrollbarState::default(),
+            entries: vec![],
+            page_len: 0,
+            rows: Rect::default(),
+            scrollbar: Rect::default(),
+        }
+    }
+
+    /// The index of the selected group.
+    pub fn selected_group(&self) -> Option<usize> {
+        self.state
+            .selected()
+            .and_then(|i| self.entries.get(i))
+            .map(|entry| entry.index)
+    }
+
+    fn select(&mut self, position: usize) {
+        self.state.select(Some(position));
+        self.scroll_state = self.scroll_state.position(position);
+    }
+
+    /// Selects the first row of the group at `index`, if it is shown.
+    pub fn select_group(&mut self, index: usize) -> bool {
+        match self.entries.iter().position(|entry| entry.index == index) {
+            Some(position) => {
+                self.select(position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Lists the groups of `view` in a table, or as a tree keeping the groups
+    /// of `view` and those leading to them, keeping the selected group selected.
+    pub fn update(&mut self, groups: &[ProxyGroup], view: &[usize], as_tree: bool) {
+        let selected = self.selected_group();
+        self.entries = match as_tree {
+            false => view
+                .iter()
+                .map(|&index| Entry {
+                    index,
+                    prefix: String::new(),
+                })
+                .collect(),
+            true => {
+                let nodes = prune(tree(groups), &|i| view.contains(&i));
+                let mut entries = vec![];
+                flatten(&nodes, None, &mut entries);
+                entries
+            }
+        };
+        if !selected.is_some_and(|index| self.select_group(index)) {
+            let position = self.state.selected().unwrap_or(0);
+            self.select(position.min(self.entries.len().saturating_sub(1)));
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        area: Rect,
+        buf: &mut Buffer,
+        groups: &[ProxyGroup],
+        matched: impl Fn(&ProxyGroup) -> Vec<Vec<usize>>,
+        theme: &Theme,
+    ) {
+        self.rows = Rect::default();
+        self.scrollbar = Rect::default();
+        if self.entries.is_empty() {
+            buf.set_string(
+                area.left(),
+                area.top(),
+                if groups.is_empty() {
+                    "No groups available"
+                } else {
+                    "No matching groups"
+                },
+                Style::new().bold(),
+            );
+            return;
+        }
+
+        let [table_area, scrollbar_area] = Layout::horizontal([
+            ratatui::layout::Constraint::Percentage(100),
+            ratatui::layout::Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let rows: Vec<Row> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let group = &groups[entry.index];
+                let matched = matched(group);
+                let mut name = Line::styled(entry.prefix.clone(), theme.muted());
+                name.extend(search::highlight(&group.name, &matched[0], theme));
+                Row::new(vec![
+                    Cell::from(name),
+                    Cell::from(search::highlight(
+                        group.proxy_type.str(),
+                        &matched[1],
+                        theme,
+                    )),
+                    Cell::from(group.now.clone().unwrap_or_default()),
+                    Cell::from(Line::styled(
+                        group.latency.map_or("--".to_string(), |l| format!("{l}ms")),
+                        theme.latency(group.latency),
+                    )),
+                    Cell::from(group.proxies.len().to_string()),
+                    Cell::from(group.udp.to_string()),
+                ])
+            })
+            .collect();
+        // the header and its margin take two lines
+        self.page_len = table_area.height.saturating_sub(2) as usize;
+        self.rows = Rect {
+            y: table_area.y + 2,
+            height: self.page_len as u16,
+            ..table_area
+        }
+        .intersection(table_area);
+        let widths = vec![
+            ratatui::layout::Constraint::Fill(2),
+            ratatui::layout::Constraint::Length(12),
+            ratatui::layout::Constraint::Fill(1),
+            ratatui::layout::Constraint::Length(8),
+            ratatui::layout::Constraint::Length(8),
+            ratatui::layout::Constraint::Length(5),
+        ];
+        StatefulWidget::render(
+            Table::new(rows, widths)
+                .header(
+                    Row::new(["Name", "Type", "Now", "Latency", "Proxies", "UDP"])
+                        .bold()
+                        .bottom_margin(1),
+                )
+                .row_highlight_style(Style::new().reversed().add_modifier(theme.selection))
+                .highlight_symbol(">>"),
+            table_area,
+            buf,
+            &mut self.state,
+        );
+
+        self.scroll_state = self.scroll_state.content_length(self.entries.len());
+        StatefulWidget::render(
+            Scrollbar::default()
+                .thumb_style(Style::default().fg(theme.accent))
+                .begin_symbol(Some("↑"))
+                .end_symbol(Some("↓")),
+            scrollbar_area,
+            buf,
+            &mut self.scroll_state,
+        );
+        self.scrollbar = scrollbar_area;
+    }
+}
+
+impl Navigable for GroupList {
+    const GRID: bool = false;
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+    fn selected(&self) -> usize {
+        self.state.selected().unwrap_or(0)
+    }
+    fn select(&mut self, position: usize) {
+        GroupList::select(self, position);
+    }
+    fn row_len(&self) -> usize {
+        1
+    }
+    fn page_len(&self) -> usize {
+        self.page_len
+    }
+}
+
+impl Clickable for GroupList {
+    fn item_at(&self, position: Position) -> Option<usize> {
+        if !self.rows.contains(position) {
+            return None;
+        }
+        let row = self.state.offset() + (position.y - self.rows.y) as usize;
+        (row < self.entries.len()).then_some(row)
+    }
+    fn scrollbar(&self) -> Rect {
+        self.scrollbar
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{ProxyType, SelectableProxy};
+
+    fn group(name: &str, proxies: &[(&str, ProxyType)]) -> ProxyGroup {
+        ProxyGroup {
+            name: name.to_string(),
+            now: None,
+            proxy_type: ProxyType::Selector,
+            udp: true,
+            latency: None,
+            proxies: proxies
+                .iter()
+                .map(|&(name, proxy_type)| SelectableProxy {
+                    name: name.to_string(),
+                    udp: true,
+                    proxy_type,
+                    latency: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn lines(list: &GroupList, groups: &[ProxyGroup]) -> Vec<String> {
+        list.entries
+            .iter()
+            .map(|e| format!("{}{}", e.prefix, groups[e.index].name))
+            .collect()
+    }
+
+    #[test]
+    fn test_tree() {
+        use ProxyType::*;
+        let groups = [
+            group("Proxy", &[("Auto", URLTest), ("HK", Vmess)]),
+            group("Auto", &[("HK", Vmess), ("JP", Vmess)]),
+            group("Streaming", &[("Proxy", Selector), ("Auto", URLTest)]),
+            group("GLOBAL", &[("Proxy", Selector), ("Streaming", Selector)]),
+            // only reachable through their cycle
+            group("A", &[("B", Selector)]),
+            group("B", &[("A", Selector)]),
+        ];
+        let mut list = GroupList::new();
+        list.update(&groups, &[0, 1, 2, 3, 4, 5], true);
+        assert_eq!(
+            lines(&list, &groups),
+            [
+                "GLOBAL",
+                "├─ Proxy",
+                "│  └─ Auto",
+                "└─ Streaming",
+                "   ├─ Proxy",
+                "   │  └─ Auto",
+                "   └─ Auto",
+                "A",
+                "└─ B",
+            ]
+        );
+
+        // the groups leading to a match stay
+        list.select_group(2);
+        list.update(&groups, &[1], true);
+        assert_eq!(
+            lines(&list, &groups),
+            [
+                "GLOBAL",
+                "├─ Proxy",
+                "│  └─ Auto",
+                "└─ Streaming",
+                "   ├─ Proxy",
+                "   │  └─ Auto",
+                "   └─ Auto",
+            ]
+        );
+        assert_eq!(list.selected_group(), Some(2));
+
+        // a hidden group leaves the selection on the closest row
+        list.update(&groups, &[3, 1], false);
+        assert_eq!(lines(&list, &groups), ["GLOBAL", "Auto"]);
+        assert_eq!(list.selected_group(), Some(1));
+    }
+}
@@ -0,0 +1,174 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Widget},
+};
+
+use super::{help, popup};
+use crate::{
+    backend::{ProxyGroup, ProxyType},
+    keymap::{Action, Context, Keymap},
+    theme::Theme,
+};
+
+/// A popup choosing the selector group to use a proxy in.
+#[derive(Debug)]
+pub struct GroupPicker {
+    proxy: String,
+    /// The selector groups having the proxy, with their current proxy.
+    groups: Vec<(String, Option<String>)>,
+    selected: usize,
+}
+
+impl GroupPicker {
+    /// The selector groups of `groups` having `proxy`, `None` if there are none.
+    pub fn new(proxy: &str, groups: &[ProxyGroup]) -> Option<Self> {
+        let groups: Vec<(String, Option<String>)> = groups
+            .iter()
+            .filter(|g| matches!(g.proxy_type, ProxyType::Selector))
+            .filter(|g| g.proxies.iter().any(|p| p.name == proxy))
+            .map(|g| (g.name.clone(), g.now.clone()))
+            .collect();
+        (!groups.is_empty()).then(|| Self {
+            proxy: proxy.to_string(),
+            groups,
+            selected: 0,
+        })
+    }
+
+    pub fn proxy(&self) -> &str {
+        &self.proxy
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        self.selected = (self.selected + 1).min(self.groups.len() - 1);
+    }
+
+    /// The group to use the proxy in.
+    pub fn chosen(&self) -> &str {
+        &self.groups[self.selected].0
+    }
+
+    /// The hints while the popup is open over `context`.
+    pub fn hints(&self, keymap: &Keymap, context: Context, theme: &Theme) -> Line<'static> {
+        let keys = |action| help::keys(keymap, context, action);
+        let select = [Action::Down, Action::Up]
+            .into_iter()
+            .filter_map(keys)
+            .collect::<Vec<_>>()
+            .join("/");
+        let hints = [
+            ((!select.is_empty()).then_some(select), "select"),
+            (keys(Action::Select), "use"),
+            (keys(Action::Back), "close"),
+        ];
+        help::line(
+            hints
+                .into_iter()
+                .filter_map(|(keys, description)| Some((keys?, description))),
+            theme,
+        )
+    }
+
+    pub fn draw(&mut self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let popup = popup::centered(area, buf, 48, self.groups.len() as u16 + 2);
+        let block = Block::bordered()
+            .title(format!(" Use {} in ", self.proxy))
+            .border_style(Style::new().fg(theme.accent));
+        let inner = block.inner(popup);
+        block.render(popup, buf);
+
+        // keep the selection in sight
+        let offset = (self.selected + 1).saturating_sub(inner.height as usize);
+        for (row, (group, now)) in self
+            .groups
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(inner.height as usize)
+        {
+            let mut line = Line::from(format!("{group:<20}"));
+            line.push_span(match now {
+                Some(now) if *now == self.proxy => {
+                    Span::styled("already in use", Style::new().fg(theme.highlight))
+                }
+                Some(now) => Span::styled(format!("now {now}"), theme.muted()),
+                None => Span::raw(""),
+            });
+            if row == self.selected {
+                line = line.reversed();
+            }
+            let y = inner.y + (row - offset) as u16;
+            line.render(Rect::new(inner.x, y, inner.width, 1), buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::SelectableProxy;
+
+    fn group(name: &str, proxy_type: ProxyType, proxies: &[&str]) -> ProxyGroup {
+        ProxyGroup {
+            name: name.to_string(),
+            now: proxies.first().map(|p| p.to_string()),
+            proxy_type,
+            udp: true,
+            latency: None,
+            proxies: proxies
+                .iter()
+                .map(|name| SelectableProxy {
+                    name: name.to_string(),
+                    udp: true,
+                    proxy_type: ProxyType::Vmess,
+                    latency: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_picker() {
+        let groups = [
+            group("Auto", ProxyType::URLTest, &["HK 01", "JP 01"]),
+            group("Proxy", ProxyType::Selector, &["HK 01", "JP 01"]),
+            group("Streaming", ProxyType::Selector, &["US 01", "JP 01"]),
+        ];
+        assert!(GroupPicker::new("SG 01", &groups).is_none());
+        // only selectors can be chosen in
+        let picker = GroupPicker::new("HK 01", &groups).unwrap();
+        assert_eq!(picker.groups.len(), 1);
+
+        let mut picker = GroupPicker::new("JP 01", &groups).unwrap();
+        picker.down();
+        picker.down();
+        assert_eq!(picker.chosen(), "Streaming");
+        picker.up();
+        assert_eq!(picker.chosen(), "Proxy");
+        picker.up();
+        assert_eq!(picker.chosen(), "Proxy");
+
+        let hints = picker.hints(
+            &Keymap::default(),
+            Context::ProviderProxies,
+            &Theme::default(),
+        );
+        assert_eq!(
+            hints.to_string(),
+            "j/↓/k/↑ select  enter/space use  esc close"
+        );
+
+        let area = Rect::new(0, 0, 48, 4);
+        let mut buf = Buffer::empty(area);
+        picker.draw(area, &mut buf, &Theme::default());
+        let rows: String = buf.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(rows.contains("Proxy               now HK 01"));
+    }
+}
@@ -0,0 +1,172 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use super::gauge::Gauge;
+use crate::{
+    backend::ProxyGroup,
+    theme::{LatencyQuality, LatencyThresholds, Theme},
+};
+
+/// The qualities in the histogram, from left to right.
+const BUCKETS: [LatencyQuality; 4] = [
+    LatencyQuality::Good,
+    LatencyQuality::Ok,
+    LatencyQuality::Slow,
+    LatencyQuality::Failed,
+];
+
+/// The members of a group by the quality of their last test.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Members {
+    /// The count of each of [`BUCKETS`].
+    buckets: [usize; 4],
+    untested: usize,
+}
+
+impl Members {
+    fn count(group: &ProxyGroup, thresholds: &LatencyThresholds) -> Self {
+        let mut members = Self::default();
+        for proxy in &group.proxies {
+            match BUCKETS
+                .iter()
+                .position(|&q| q == thresholds.quality(proxy.latency))
+            {
+                Some(bucket) => members.buckets[bucket] += 1,
+                None => members.untested += 1,
+            }
+        }
+        members
+    }
+
+    /// Members answering their last test.
+    fn alive(&self) -> usize {
+        self.buckets[..3].iter().sum()
+    }
+
+    /// Members failing their last test.
+    fn dead(&self) -> usize {
+        self.buckets[3]
+    }
+}
+
+/// One line on a group card: a histogram of the member latencies, the count
+/// of alive and dead members, and UDP support.
+#[derive(Debug)]
+pub struct GroupPreview<'a> {
+    group: &'a ProxyGroup,
+    theme: &'a Theme,
+}
+
+impl<'a> GroupPreview<'a> {
+    pub fn new(group: &'a ProxyGroup, theme: &'a Theme) -> Self {
+        Self { group, theme }
+    }
+}
+
+impl Widget for GroupPreview<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        let theme = self.theme;
+        let members = Members::count(self.group, &theme.thresholds);
+        let highest = members.buckets.iter().copied().max().unwrap_or(0);
+        let colors = [
+            theme.latency_good,
+            theme.latency_ok,
+            theme.latency_slow,
+            theme.latency_failed,
+        ];
+        for (i, (&count, color)) in members.buckets.iter().zip(colors).enumerate() {
+            let column = Rect {
+                x: area.x + i as u16,
+                width: 1,
+                height: 1,
+                ..area
+            }
+            .intersection(area);
+            let ratio = match highest {
+                0 => 0.0,
+                highest => count as f64 / highest as f64,
+            };
+            Gauge::vertical().ratio(ratio).fg(color).render(column, buf);
+        }
+
+        let mut line = Line::from(vec![
+            Span::styled(
+                format!("{} up", members.alive()),
+                ratatui::style::Style::new().fg(theme.latency_good),
+            ),
+            Span::raw(" "),
+            Span::styled(
+                format!("{} down", members.dead()),
+                match members.dead() {
+                    0 => theme.muted(),
+                    _ => ratatui::style::Style::new().fg(theme.latency_failed),
+                },
+            ),
+        ]);
+        if self.group.udp {
+            line.push_span(Span::raw(" UDP").bold());
+        }
+        let text = Rect {
+            x: area.x + BUCKETS.len() as u16 + 1,
+            width: area.width.saturating_sub(BUCKETS.len() as u16 + 1),
+            ..area
+        }
+        .intersection(area);
+        line.render(text, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{ProxyType, SelectableProxy};
+
+    #[test]
+    fn test_preview() {
+        let latencies = [Some(40), Some(90), Some(350), Some(0), None, Some(60)];
+        let group = ProxyGroup {
+            name: "Auto".to_string(),
+            now: None,
+            proxy_type: ProxyType::URLTest,
+            udp: true,
+            latency: Some(40),
+            proxies: latencies
+                .iter()
+                .enumerate()
+                .map(|(i, &latency)| SelectableProxy {
+                    name: format!("HK 0{i}"),
+                    udp: true,
+                    proxy_type: ProxyType::Vmess,
+                    latency,
+                })
+                .collect(),
+        };
+        let theme = Theme::default().with_thresholds(LatencyThresholds { good: 100, ok: 300 });
+        let members = Members::count(&group, &theme.thresholds);
+        assert_eq!(members.buckets, [3, 0, 1, 1]);
+        assert_eq!(members.untested, 1);
+        assert_eq!((members.alive(), members.dead()), (4, 1));
+
+        let area = Rect::new(0, 0, 20, 1);
+        let mut buf = Buffer::empty(area);
+        GroupPreview::new(&group, &theme).render(area, &mut buf);
+        let line: String = buf.content().iter().map(|cell| cell.symbol()).collect();
+        assert_eq!(line, "█ ▃▃ 4 up 1 down UDP");
+        assert_eq!(buf[(0, 0)].fg, theme.latency_good);
+        assert_eq!(buf[(3, 0)].fg, theme.latency_failed);
+
+        // a card narrower than the line cuts it
+        let area = Rect::new(0, 0, 3, 1);
+        let mut buf = Buffer::empty(area);
+        GroupPreview::new(&group, &theme).render(area, &mut buf);
+        assert_eq!(buf[(0, 0)].symbol(), "█");
+    }
+}
@@ -0,0 +1,141 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Widget},
+};
+
+use super::popup;
+use crate::{
+    keymap::{Action, Context, Keymap},
+    theme::Theme,
+};
+
+/// The keys of `action` in `context`, such as `k/↑`.
+pub(super) fn keys(keymap: &Keymap, context: Context, action: Action) -> Option<String> {
+    let keys = keymap.keys(context, action);
+    if keys.is_empty() {
+        return None;
+    }
+    Some(
+        keys.iter()
+            .map(|k| k.to_string())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+/// Key hints of `(keys, description)` pairs, such as `r test group  s sort`.
+pub fn line<'a>(
+    hints: impl IntoIterator<Item = (String, &'a str)>,
+    theme: &Theme,
+) -> Line<'static> {
+    let mut line = Line::default();
+    for (keys, description) in hints {
+        if !line.spans.is_empty() {
+            line.push_span("  ");
+        }
+        line.push_span(Span::styled(keys, Style::new().fg(theme.accent).bold()));
+        line.push_span(format!(" {description}"));
+    }
+    line
+}
+
+/// The hints of the bindings valid in `context`.
+pub fn hints(keymap: &Keymap, context: Context, theme: &Theme) -> Line<'static> {
+    line(
+        context
+            .hints()
+            .into_iter()
+            .filter_map(|action| Some((keys(keymap, context, action)?, action.description()))),
+        theme,
+    )
+}
+
+fn title(context: Context) -> &'static str {
+    match context {
+        Context::Global => "Everywhere",
+        Context::Groups => "Group page",
+        Context::GroupProxies => "Proxies of a group",
+        Context::Providers => "Provider page",
+        Context::ProviderDetail => "Subscription of a provider",
+        Context::ProviderProxies => "Proxies of a provider",
+        Context::Palette => "Go to",
+    }
+}
+
+/// A popup listing every action of a page with its keys.
+#[derive(Debug)]
+pub struct Help {
+    context: Context,
+    offset: usize,
+}
+
+impl Help {
+    pub fn new(context: Context) -> Self {
+        Self { context, offset: 0 }
+    }
+
+    pub fn context(&self) -> Context {
+        self.context
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.offset = self.offset.saturating_sub(1);
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.offset += 1;
+    }
+
+    /// The hints while the popup is open.
+    pub fn hints(&self, keymap: &Keymap, theme: &Theme) -> Line<'static> {
+        let scroll = [Action::Down, Action::Up]
+            .into_iter()
+            .filter_map(|action| keys(keymap, self.context, action))
+            .collect::<Vec<_>>()
+            .join("/");
+        line(
+            [(scroll, "scroll"), ("other keys".to_string(), "close")],
+            theme,
+        )
+    }
+
+    fn lines(&self, keymap: &Keymap, theme: &Theme) -> Vec<Line<'static>> {
+        let mut lines = vec![];
+        for context in [self.context, Context::Global] {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::from(title(context)).bold().underlined());
+            for action in context.actions() {
+                let keys = keys(keymap, self.context, *action).unwrap_or("--".to_string());
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("{keys:>16}  "),
+                        Style::new().fg(theme.accent).bold(),
+                    ),
+                    Span::raw(action.description()),
+                ]));
+            }
+        }
+        lines
+    }
+
+    pub fn draw(&mut self, area: Rect, buf: &mut Buffer, keymap: &Keymap, theme: &Theme) {
+        let lines = self.lines(keymap, theme);
+        let popup = popup::centered(area, buf, 48, lines.len() as u16 + 2);
+        // stop scrolling once the last line is in sight
+        let height = popup.height.saturating_sub(2) as usize;
+        self.offset = self.offset.min(lines.len().saturating_sub(height));
+        Paragraph::new(lines)
+            .scroll((self.offset as u16, 0))
+            .block(
+                Block::bordered()
+                    .title(" Keys ")
+                    .border_style(Style::new().fg(theme.accent)),
+            )
+            .render(popup, buf);
+    }
+}
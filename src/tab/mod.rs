@@ -1,30 +1,64 @@
-use std::sync::LazyLock;
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
 
+use bulk::{Bulk, Operation};
 use bytesize::ByteSize;
 use card_page::CardPage;
 use chrono::{DateTime, TimeZone, Utc};
-use crossterm::event::KeyModifiers;
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use gauge::Gauge;
+use group_list::GroupList;
+use group_picker::GroupPicker;
+use group_preview::GroupPreview;
+use help::Help;
 use humanize_duration::prelude::DurationExt;
+use navigation::Motion;
+use palette::{Palette, Target};
+use proxy_diff::{DiffPopup, ProxyDiff};
 use ratatui::{
     buffer::Buffer,
-    layout::{Layout, Rect},
-    style::{Color, Stylize},
-    text::{Line, Text},
+    layout::{Layout, Position, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
     widgets::{Block, Paragraph, Tabs, Widget, Wrap},
 };
+use search::Filtered;
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::{EnumIter, IntoStaticStr};
-use vertical_gauge::VerticalGauge;
 
-use crate::backend::{
-    Provider, ProxyGroup, SelectableProxy, get_groups_data, get_providers_data, latency_test_group,
-    latency_test_provider, latency_test_proxy, refresh_data, select_proxy, update_proxy_provider,
+use crate::{
+    alert::{Alert, AlertThresholds},
+    backend::{Error, Provider, ProxyGroup, ProxyType, Result, SelectableProxy, Store},
+    keymap::{Action, Context, Keymap, Resolved},
+    state::State,
+    theme::Theme,
+    usage::{Forecast, UsageHistory},
 };
 
+mod bulk;
 mod card;
 mod card_page;
+mod gauge;
+mod group_list;
+mod group_picker;
+mod group_preview;
+mod help;
+mod mouse;
+mod navigation;
+mod palette;
+mod popup;
+mod provider_detail;
+mod proxy_diff;
 mod proxy_page;
-mod vertical_gauge;
+mod search;
+mod sweep;
+
+pub use sweep::SweepConfig;
 
 #[derive(Debug, IntoStaticStr, EnumIter, Eq, PartialEq, Clone, Copy)]
 pub enum Tab {
@@ -48,6 +82,19 @@ impl Tab {
         let next_idx = (variants.iter().position(|v| v == self).unwrap() + 1) % variants.len();
         *self = variants[next_idx];
     }
+    /// The tab whose title is at `column` of `titles` drawn from `x`.
+    fn at(x: u16, column: u16, titles: &[Line]) -> Option<Self> {
+        let mut start = x;
+        for (tab, title) in Self::variants().iter().zip(titles) {
+            // a title is padded by a space on each side, then followed by the divider
+            let end = start + title.width() as u16 + 2;
+            if (start..end).contains(&column) {
+                return Some(*tab);
+            }
+            start = end + 1;
+        }
+        None
+    }
     fn prev(&mut self) {
         let variants = Self::variants();
         let prev_idx = (variants.iter().position(|v| v == self).unwrap() + variants.len() - 1)
@@ -56,208 +103,1252 @@ impl Tab {
     }
 }
 
+/// The size of the cards of a page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CardSize {
+    pub height: u16,
+    /// The narrowest a card gets before a row holds one card less.
+    pub width: u16,
+}
+
+impl CardSize {
+    /// Borders and a line of text.
+    const MIN_HEIGHT: u16 = 3;
+    const MIN_WIDTH: u16 = 10;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CardSizes {
+    pub groups: CardSize,
+    pub providers: CardSize,
+}
+
+impl Default for CardSizes {
+    fn default() -> Self {
+        Self {
+            groups: CardSize {
+                height: 4,
+                width: 25,
+            },
+            providers: CardSize {
+                height: 6,
+                width: 40,
+            },
+        }
+    }
+}
+
+impl CardSizes {
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        for (page, size) in [("groups", self.groups), ("providers", self.providers)] {
+            if size.height < CardSize::MIN_HEIGHT || size.width < CardSize::MIN_WIDTH {
+                return Err(format!(
+                    "the cards of `{page}` must be at least {} high and {} wide",
+                    CardSize::MIN_HEIGHT,
+                    CardSize::MIN_WIDTH
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How the group page lists the groups, switched with `v`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupView {
+    #[default]
+    Cards,
+    /// Name, type, `now`, latency, member count and UDP.
+    Table,
+    /// The groups nested under the groups having them as a proxy.
+    Tree,
+}
+
+impl GroupView {
+    fn next(self) -> Self {
+        match self {
+            GroupView::Cards => GroupView::Table,
+            GroupView::Table => GroupView::Tree,
+            GroupView::Tree => GroupView::Cards,
+        }
+    }
+}
+
+/// The narrowest the group tab shows the groups and the proxies side by side.
+const SPLIT_MIN_WIDTH: u16 = 80;
+
+/// Clicks on the same item closer than this are a double click.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// A popup taking the keys until it is closed.
+#[derive(Debug)]
+enum Overlay {
+    Palette(Palette),
+    Help(Help),
+    /// The proxies changed by the update of a provider.
+    Diff(DiffPopup),
+    /// Chooses the group to select a proxy of a provider in.
+    GroupPicker(GroupPicker),
+    /// Asks whether to select `proxy` in `group`.
+    ConfirmSelect {
+        group: String,
+        proxy: String,
+    },
+}
+
 #[derive(Debug)]
 pub struct BoardWidget {
+    store: Store,
+    /// The error or the outcome of the last action, shown until the next key press.
+    message: Option<String>,
+    overlay: Option<Overlay>,
+    keymap: Keymap,
+    theme: Theme,
+    quit: bool,
+    /// Where the tab titles were last drawn.
+    tab_pane: Rect,
+    drag: mouse::Drag,
+    /// The last item clicked, to recognize a double click.
+    last_click: Option<(Instant, Context, usize)>,
+    /// Where the state is saved when it changes.
+    state_path: Option<PathBuf>,
+    usage: UsageHistory,
+    alerts: AlertThresholds,
+    sweep: SweepConfig,
     current_tab: Tab,
     group_tab_state: ProxyTabState,
     provider_tab_state: ProviderTab,
 }
 
 impl BoardWidget {
-    pub fn new() -> Self {
+    pub fn new(store: Store) -> Self {
+        let cards = CardSizes::default();
         Self {
+            store,
+            message: None,
+            overlay: None,
+            keymap: Keymap::default(),
+            theme: Theme::default(),
+            quit: false,
+            tab_pane: Rect::default(),
+            drag: mouse::Drag::default(),
+            last_click: None,
+            state_path: None,
+            usage: UsageHistory::default(),
+            alerts: AlertThresholds::default(),
+            sweep: SweepConfig::default(),
             current_tab: Tab::Group,
             group_tab_state: ProxyTabState {
-                group_page: CardPage::new(4, 25),
+                group_page: CardPage::new(cards.groups.height, cards.groups.width),
+                group_filter: Filtered::default(),
+                view: GroupView::default(),
+                group_list: GroupList::new(),
                 current_page: ProxyTabStatePage::Group,
                 proxy_page: proxy_page::ProxyPage::new(),
+                split: false,
+                panes: None,
             },
             provider_tab_state: ProviderTab {
                 current_page: ProviderTabState::Providers,
-                provider_page: CardPage::new(6, 40),
+                provider_page: CardPage::new(cards.providers.height, cards.providers.width),
+                provider_filter: Filtered::default(),
                 proxy_page: proxy_page::ProxyPage::new(),
+                marked: HashSet::new(),
+                bulk: None,
+                before_bulk: vec![],
+                diffs: HashMap::new(),
             },
         }
     }
-    pub fn draw_tab_pane(&self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
-        Tabs::new(Tab::all_names())
-            .highlight_style(ratatui::style::Style::default().fg(ratatui::style::Color::Yellow))
+    /// Uses `keymap` instead of the default one, reporting its conflicts.
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn with_keymap(mut self, keymap: Keymap) -> Self {
+        let conflicts = keymap.conflicts();
+        self.message = match conflicts.as_slice() {
+            [] => None,
+            [conflict] => Some(format!("key conflict: {conflict}")),
+            [conflict, rest @ ..] => Some(format!(
+                "key conflict: {conflict} (and {} more)",
+                rest.len()
+            )),
+        };
+        self.keymap = keymap;
+        self
+    }
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn with_card_sizes(mut self, cards: CardSizes) -> Self {
+        self.group_tab_state.group_page = CardPage::new(cards.groups.height, cards.groups.width);
+        self.provider_tab_state.provider_page =
+            CardPage::new(cards.providers.height, cards.providers.width);
+        self
+    }
+    /// Restores `state`, saving it to `path` when it changes.
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn with_state(mut self, state: State, path: Option<PathBuf>) -> Self {
+        self.group_tab_state.view = state.group_view;
+        self.group_tab_state.split = state.split;
+        self.usage = state.usage;
+        self.state_path = path;
+        self.record_usage();
+        self
+    }
+    pub fn state(&self) -> State {
+        State {
+            group_view: self.group_tab_state.view,
+            split: self.group_tab_state.split,
+            usage: self.usage.clone(),
+        }
+    }
+    /// Flags the subscriptions reaching `thresholds`.
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn with_alerts(mut self, thresholds: AlertThresholds) -> Self {
+        self.alerts = thresholds;
+        self
+    }
+    /// Tests the proxies of a page `config.concurrency` at a time.
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn with_sweep(mut self, config: SweepConfig) -> Self {
+        self.sweep = config;
+        self
+    }
+    /// Whether work runs in the background, in which case [`Self::tick`] should
+    /// be called regularly to show its progress.
+    pub fn is_busy(&self) -> bool {
+        self.provider_tab_state.is_busy()
+            || self.group_tab_state.proxy_page.is_sweeping()
+            || self.provider_tab_state.proxy_page.is_sweeping()
+    }
+    /// Takes the progress of the background work, reporting it once it is over.
+    pub fn tick(&mut self) {
+        match self.provider_tab_state.poll_bulk(&mut self.store) {
+            Ok(Some(summary)) => {
+                self.message = Some(summary);
+                self.record_usage();
+            }
+            Ok(None) => {}
+            Err(e) => self.message = Some(e.to_string()),
+        }
+        let swept = [
+            &mut self.group_tab_state.proxy_page,
+            &mut self.provider_tab_state.proxy_page,
+        ]
+        .map(|page| page.poll_sweep());
+        for summary in swept.into_iter().flatten() {
+            // the controller keeps the new delays in the history of the proxies
+            self.message = Some(match self.store.refresh() {
+                Ok(()) => summary,
+                Err(e) => e.to_string(),
+            });
+        }
+        self.provider_tab_state.retain_marks(&self.store);
+    }
+    /// The subscriptions close to their expiry or quota.
+    pub fn alerts(&self) -> Vec<Alert> {
+        self.alerts.check(self.store.providers(), Utc::now())
+    }
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+    /// The names of the tabs, the provider one with a badge counting the alerts.
+    fn tab_titles(&self) -> Vec<Line<'static>> {
+        let alerts = self.alerts().len();
+        Tab::variants()
+            .iter()
+            .zip(Tab::all_names())
+            .map(|(tab, name)| match tab {
+                Tab::Provider if alerts > 0 => Line::from(vec![
+                    Span::raw(name),
+                    Span::raw(" "),
+                    Span::styled(
+                        format!(" {alerts} "),
+                        Style::new().fg(self.theme.error).reversed(),
+                    ),
+                ]),
+                _ => Line::from(name),
+            })
+            .collect()
+    }
+    pub fn draw_tab_pane(
+        &mut self,
+        area: ratatui::layout::Rect,
+        buf: &mut ratatui::buffer::Buffer,
+    ) {
+        self.tab_pane = area;
+        Tabs::new(self.tab_titles())
+            .highlight_style(ratatui::style::Style::default().fg(self.theme.highlight))
             .divider("|")
             .select(Tab::variants().iter().position(|n| n == &self.current_tab))
             .render(area, buf);
     }
     pub fn draw_tab(&mut self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
         match self.current_tab {
-            Tab::Group => self.group_tab_state.draw(area, buf),
-            Tab::Provider => self.provider_tab_state.draw(area, buf),
+            Tab::Group => self
+                .group_tab_state
+                .draw(area, buf, &self.store, &self.theme),
+            Tab::Provider => {
+                self.provider_tab_state
+                    .draw(area, buf, &self.store, &self.usage, &self.theme)
+            }
+        }
+        match &mut self.overlay {
+            Some(Overlay::Palette(palette)) => palette.draw(area, buf, &self.theme),
+            Some(Overlay::Help(help)) => help.draw(area, buf, &self.keymap, &self.theme),
+            Some(Overlay::Diff(diff)) => diff.draw(area, buf, &self.theme),
+            Some(Overlay::GroupPicker(picker)) => picker.draw(area, buf, &self.theme),
+            Some(Overlay::ConfirmSelect { group, proxy }) => popup::draw_question(
+                area,
+                buf,
+                &format!("Select {proxy} in {group}?"),
+                &self.theme,
+            ),
+            None => {}
         }
     }
     pub fn key_event(&mut self, key: crossterm::event::KeyEvent) {
-        match key.code {
-            crossterm::event::KeyCode::Tab => self.current_tab.next(),
-            crossterm::event::KeyCode::BackTab => self.current_tab.prev(),
-            _ => match self.current_tab {
-                Tab::Group => self.group_tab_state.key_event(key),
-                Tab::Provider => self.provider_tab_state.key_event(key),
+        self.message = None;
+        let result = if let Some(overlay) = self.overlay.take() {
+            self.overlay_key_event(overlay, key)
+        } else if self.is_searching() {
+            match self.current_tab {
+                Tab::Group => self.group_tab_state.type_key(key, &self.store),
+                Tab::Provider => self.provider_tab_state.type_key(key, &self.store),
+            }
+            Ok(())
+        } else {
+            match self.keymap.resolve(self.context(), key) {
+                Resolved::Action { action, count } => self.action(action, count),
+                Resolved::Pending | Resolved::Unbound => Ok(()),
+            }
+        };
+        if let Err(e) = result {
+            self.message = Some(e.to_string());
+        }
+        self.provider_tab_state.retain_marks(&self.store);
+        self.record_usage();
+    }
+    /// Handles clicks on the tabs and the items, the wheel and scrollbar drags.
+    pub fn mouse_event(&mut self, event: MouseEvent) {
+        if self.overlay.is_some() {
+            return;
+        }
+        let position = Position::new(event.column, event.row);
+        if let MouseEventKind::Down(MouseButton::Left) = event.kind {
+            self.message = None;
+            if self.tab_pane.contains(position) {
+                if let Some(tab) = Tab::at(self.tab_pane.x, event.column, &self.tab_titles()) {
+                    self.current_tab = tab;
+                }
+                return;
+            }
+        }
+
+        let clicked = match self.current_tab {
+            Tab::Group => self.group_tab_state.mouse_event(event, &mut self.drag),
+            Tab::Provider => self.provider_tab_state.mouse_event(event, &mut self.drag),
+        };
+        // a click may move the focus to another pane
+        let context = self.context();
+        let Some(clicked) = clicked else {
+            return;
+        };
+        let now = Instant::now();
+        match self.last_click.take() {
+            Some((time, c, item))
+                if c == context && item == clicked && now - time < DOUBLE_CLICK =>
+            {
+                // a double click does what enter does
+                let action = match context {
+                    Context::Groups | Context::Providers => Action::Open,
+                    Context::GroupProxies | Context::ProviderProxies => Action::Select,
+                    Context::Global | Context::ProviderDetail | Context::Palette => return,
+                };
+                if let Err(e) = self.action(action, None) {
+                    self.message = Some(e.to_string());
+                }
+                self.provider_tab_state.retain_marks(&self.store);
+            }
+            _ => self.last_click = Some((now, context, clicked)),
+        }
+    }
+    fn action(&mut self, action: Action, count: Option<usize>) -> Result<()> {
+        match action {
+            Action::Quit => self.quit = true,
+            Action::NextTab => self.current_tab.next(),
+            Action::PreviousTab => self.current_tab.prev(),
+            Action::GoTo => self.overlay = Some(Overlay::Palette(Palette::new(&self.store))),
+            Action::Help => self.overlay = Some(Overlay::Help(Help::new(self.context()))),
+            Action::View if self.current_tab == Tab::Group => {
+                self.group_tab_state.cycle_view(&self.store);
+                self.save_state();
+            }
+            Action::Split if self.current_tab == Tab::Group => {
+                self.group_tab_state.split = !self.group_tab_state.split;
+                self.save_state();
+            }
+            Action::Select if self.current_tab == Tab::Provider => {
+                let Some(proxy) = self.provider_tab_state.current_proxy(&self.store) else {
+                    return Ok(());
+                };
+                if self.store.client().is_read_only() {
+                    return Err(Error::ReadOnly);
+                }
+                match GroupPicker::new(&proxy, self.store.groups()) {
+                    Some(picker) => self.overlay = Some(Overlay::GroupPicker(picker)),
+                    None => self.message = Some(format!("no selector group has {proxy}")),
+                }
+            }
+            Action::Sweep => {
+                let (client, concurrency) = (self.store.shared_client(), self.sweep.concurrency);
+                let Some((page, proxies)) = self.current_proxies() else {
+                    return Ok(());
+                };
+                if client.is_read_only() {
+                    return Err(Error::ReadOnly);
+                }
+                if !page.is_sweeping() && !proxies.is_empty() {
+                    page.start_sweep(client, proxies, concurrency);
+                }
+            }
+            // esc stops a sweep before it leaves the page
+            Action::Back
+                if self
+                    .current_proxies()
+                    .is_some_and(|(page, _)| page.is_sweeping()) =>
+            {
+                self.message = self
+                    .current_proxies()
+                    .and_then(|(page, _)| page.cancel_sweep());
+                self.store.refresh()?;
+            }
+            Action::UpdateProvider if self.current_tab == Tab::Provider => {
+                if let Some(diff) = self.provider_tab_state.update_provider(&mut self.store)? {
+                    self.overlay = Some(Overlay::Diff(DiffPopup::new(diff)));
+                }
+            }
+            Action::UpdateAll | Action::HealthcheckAll if self.current_tab == Tab::Provider => {
+                if self.store.client().is_read_only() {
+                    return Err(Error::ReadOnly);
+                }
+                if self.provider_tab_state.is_busy() {
+                    self.message = Some("wait for the current bulk action to finish".to_string());
+                    return Ok(());
+                }
+                let operation = match action {
+                    Action::UpdateAll => Operation::Update,
+                    _ => Operation::Healthcheck,
+                };
+                let names = self.provider_tab_state.bulk_targets(&self.store);
+                if names.is_empty() {
+                    self.message = Some(match operation {
+                        Operation::Update => "no providers to update".to_string(),
+                        Operation::Healthcheck => "no providers to healthcheck".to_string(),
+                    });
+                    return Ok(());
+                }
+                self.provider_tab_state
+                    .start_bulk(operation, names, &self.store);
+            }
+            _ => {
+                return match self.current_tab {
+                    Tab::Group => self.group_tab_state.action(action, count, &mut self.store),
+                    Tab::Provider => self
+                        .provider_tab_state
+                        .action(action, count, &mut self.store),
+                };
+            }
+        }
+        Ok(())
+    }
+    /// The proxy page shown, with the proxies of its group or provider.
+    fn current_proxies(&mut self) -> Option<(&mut proxy_page::ProxyPage, &[SelectableProxy])> {
+        match self.current_tab {
+            Tab::Group => self.group_tab_state.current_proxies(&self.store),
+            Tab::Provider => self.provider_tab_state.current_proxies(&self.store),
+        }
+    }
+    /// Samples the traffic of the subscriptions, saving the samples.
+    fn record_usage(&mut self) {
+        if self
+            .usage
+            .record(self.store.providers(), Utc::now().timestamp())
+        {
+            self.save_state();
+        }
+    }
+    fn save_state(&mut self) {
+        if let Some(path) = &self.state_path
+            && let Err(e) = self.state().save(path)
+        {
+            self.message = Some(format!("could not save the state: {e}"));
+        }
+    }
+    /// The context of the keys, which depends on the current page.
+    fn context(&self) -> Context {
+        match self.current_tab {
+            Tab::Group => self.group_tab_state.context(),
+            Tab::Provider => self.provider_tab_state.context(),
+        }
+    }
+    fn overlay_key_event(
+        &mut self,
+        overlay: Overlay,
+        key: crossterm::event::KeyEvent,
+    ) -> Result<()> {
+        use crossterm::event::KeyCode::*;
+
+        match overlay {
+            // moves with the keys of the palette, and any other key edits the query
+            Overlay::Palette(mut palette) => match self.keymap.resolve(Context::Palette, key) {
+                Resolved::Action {
+                    action: Action::Up, ..
+                } => {
+                    palette.up();
+                    self.overlay = Some(Overlay::Palette(palette));
+                }
+                Resolved::Action {
+                    action: Action::Down,
+                    ..
+                } => {
+                    palette.down();
+                    self.overlay = Some(Overlay::Palette(palette));
+                }
+                Resolved::Action {
+                    action: Action::Open,
+                    ..
+                } => {
+                    if let Some(target) = palette.chosen() {
+                        self.go_to(target);
+                    }
+                }
+                Resolved::Action {
+                    action: Action::Back,
+                    ..
+                } => {}
+                Resolved::Pending => self.overlay = Some(Overlay::Palette(palette)),
+                Resolved::Action { .. } | Resolved::Unbound => {
+                    palette.type_key(key);
+                    self.overlay = Some(Overlay::Palette(palette));
+                }
+            },
+            // scrolls with the motion keys, and any other key closes it
+            Overlay::Help(mut help) => match self.keymap.resolve(help.context(), key) {
+                Resolved::Action {
+                    action: Action::Up, ..
+                } => {
+                    help.scroll_up();
+                    self.overlay = Some(Overlay::Help(help));
+                }
+                Resolved::Action {
+                    action: Action::Down,
+                    ..
+                } => {
+                    help.scroll_down();
+                    self.overlay = Some(Overlay::Help(help));
+                }
+                Resolved::Pending => self.overlay = Some(Overlay::Help(help)),
+                Resolved::Action { .. } | Resolved::Unbound => {}
             },
+            // the same for the changes of an update
+            Overlay::Diff(mut diff) => match self.keymap.resolve(self.context(), key) {
+                Resolved::Action {
+                    action: Action::Up, ..
+                } => {
+                    diff.scroll_up();
+                    self.overlay = Some(Overlay::Diff(diff));
+                }
+                Resolved::Action {
+                    action: Action::Down,
+                    ..
+                } => {
+                    diff.scroll_down();
+                    self.overlay = Some(Overlay::Diff(diff));
+                }
+                Resolved::Pending => self.overlay = Some(Overlay::Diff(diff)),
+                Resolved::Action { .. } | Resolved::Unbound => {}
+            },
+            // chooses with the keys of the page, ignoring the others
+            Overlay::GroupPicker(mut picker) => match self.keymap.resolve(self.context(), key) {
+                Resolved::Action {
+                    action: Action::Up, ..
+                } => {
+                    picker.up();
+                    self.overlay = Some(Overlay::GroupPicker(picker));
+                }
+                Resolved::Action {
+                    action: Action::Down,
+                    ..
+                } => {
+                    picker.down();
+                    self.overlay = Some(Overlay::GroupPicker(picker));
+                }
+                Resolved::Action {
+                    action: Action::Select,
+                    ..
+                } => {
+                    self.store
+                        .client()
+                        .select_proxy(picker.chosen(), picker.proxy())?;
+                    self.store.refresh()?;
+                }
+                Resolved::Action {
+                    action: Action::Back,
+                    ..
+                } => {}
+                Resolved::Action { .. } | Resolved::Pending | Resolved::Unbound => {
+                    self.overlay = Some(Overlay::GroupPicker(picker));
+                }
+            },
+            Overlay::ConfirmSelect { group, proxy } => {
+                if matches!(key.code, Char('y') | Enter) {
+                    self.store.client().select_proxy(&group, &proxy)?;
+                    self.store.refresh()?;
+                }
+            }
         }
+        Ok(())
+    }
+    /// Shows the page of `target` with it selected.
+    fn go_to(&mut self, target: Target) {
+        match target {
+            Target::Group(index) => {
+                self.current_tab = Tab::Group;
+                self.group_tab_state.show_group(index, &self.store);
+            }
+            Target::Proxy { group, proxy } => {
+                self.current_tab = Tab::Group;
+                self.group_tab_state.show_proxy(group, proxy, &self.store);
+                let group = &self.store.groups()[group];
+                if matches!(group.proxy_type, ProxyType::Selector) {
+                    self.overlay = Some(Overlay::ConfirmSelect {
+                        group: group.name.clone(),
+                        proxy: group.proxies[proxy].name.clone(),
+                    });
+                }
+            }
+            Target::Provider(index) => {
+                self.current_tab = Tab::Provider;
+                self.provider_tab_state.show_provider(index, &self.store);
+            }
+        }
+    }
+    /// Whether the user asked to quit.
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+    /// Whether keys go to a search query instead of their bindings.
+    pub fn is_searching(&self) -> bool {
+        if self.overlay.is_some() {
+            return true;
+        }
+        match self.current_tab {
+            Tab::Group => self.group_tab_state.is_searching(),
+            Tab::Provider => self.provider_tab_state.is_searching(),
+        }
+    }
+    /// The keys valid right now, for the bottom bar.
+    pub fn key_hints(&self) -> Line<'static> {
+        let fixed = |hints: &[(&str, &'static str)]| {
+            help::line(
+                hints.iter().map(|(keys, d)| (keys.to_string(), *d)),
+                &self.theme,
+            )
+        };
+        match &self.overlay {
+            Some(Overlay::Palette(palette)) => palette.hints(&self.keymap, &self.theme),
+            Some(Overlay::Help(help)) => help.hints(&self.keymap, &self.theme),
+            Some(Overlay::Diff(_)) => fixed(&[("↓↑", "scroll"), ("other keys", "close")]),
+            Some(Overlay::GroupPicker(picker)) => {
+                picker.hints(&self.keymap, self.context(), &self.theme)
+            }
+            Some(Overlay::ConfirmSelect { .. }) => {
+                fixed(&[("y/enter", "yes"), ("other keys", "no")])
+            }
+            None if self.is_searching() => {
+                fixed(&[("enter", "keep the filter"), ("esc", "clear the query")])
+            }
+            None => help::hints(&self.keymap, self.context(), &self.theme),
+        }
+    }
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+    pub fn is_read_only(&self) -> bool {
+        self.store.client().is_read_only()
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ProxyTabStatePage {
     Group,
     Proxy,
 }
 
+fn group_fields(group: &ProxyGroup) -> Vec<&str> {
+    vec![&group.name, group.proxy_type.str()]
+}
+
+fn provider_fields(provider: &Provider) -> Vec<&str> {
+    vec![&provider.name, &provider.vehicle_type]
+}
+
 #[derive(Debug)]
 pub struct ProxyTabState {
     current_page: ProxyTabStatePage,
     group_page: CardPage,
+    group_filter: Filtered,
+    view: GroupView,
+    /// The groups in the table and tree views.
+    group_list: GroupList,
     proxy_page: proxy_page::ProxyPage,
+    /// Shows the groups and the proxies of the selected one side by side, the
+    /// current page having the focus.
+    split: bool,
+    /// Where the groups and the proxies were last drawn side by side.
+    panes: Option<(Rect, Rect)>,
 }
 impl ProxyTabState {
-    fn get_current_group(&self) -> Option<&ProxyGroup> {
-        get_groups_data().get(self.group_page.get_current_item())
+    /// The index of the selected group.
+    fn get_current_index(&self) -> Option<usize> {
+        match self.view {
+            GroupView::Cards => self.group_filter.get(self.group_page.get_current_item()),
+            GroupView::Table | GroupView::Tree => self.group_list.selected_group(),
+        }
+    }
+    fn get_current_group<'a>(&self, store: &'a Store) -> Option<&'a ProxyGroup> {
+        self.get_current_index()
+            .and_then(|index| store.groups().get(index))
+    }
+    /// The position of the selected group among the search results.
+    fn get_current_match(&self) -> usize {
+        self.get_current_index()
+            .and_then(|index| self.group_filter.position(index))
+            .unwrap_or(0)
+    }
+    /// Selects the group at `position` of the search results, in the current view.
+    fn select_match(&mut self, position: usize, groups: &[ProxyGroup]) {
+        match self.view {
+            GroupView::Cards => self.group_page.select(position),
+            GroupView::Table | GroupView::Tree => {
+                self.group_list.update(
+                    groups,
+                    self.group_filter.view(),
+                    self.view == GroupView::Tree,
+                );
+                if let Some(index) = self.group_filter.get(position) {
+                    self.group_list.select_group(index);
+                }
+            }
+        }
+    }
+    fn show_group(&mut self, index: usize, store: &Store) {
+        self.group_filter.clear(store.groups().len());
+        // once cleared, the search results are all the groups
+        self.select_match(index, store.groups());
+        self.current_page = ProxyTabStatePage::Group;
+    }
+    /// Switches to the next [`GroupView`], keeping the selected group selected.
+    fn cycle_view(&mut self, store: &Store) {
+        let position = self.get_current_match();
+        self.view = self.view.next();
+        self.select_match(position, store.groups());
+    }
+    fn show_proxy(&mut self, group: usize, proxy: usize, store: &Store) {
+        self.show_group(group, store);
+        self.proxy_page.show(proxy, &store.groups()[group].proxies);
+        self.current_page = ProxyTabStatePage::Proxy;
+    }
+    fn context(&self) -> Context {
+        match self.current_page {
+            ProxyTabStatePage::Group => Context::Groups,
+            ProxyTabStatePage::Proxy => Context::GroupProxies,
+        }
+    }
+    fn is_searching(&self) -> bool {
+        match self.current_page {
+            ProxyTabStatePage::Group => self.group_filter.search.is_typing(),
+            ProxyTabStatePage::Proxy => self.proxy_page.is_searching(),
+        }
+    }
+    fn current_proxies<'a>(
+        &'a mut self,
+        store: &'a Store,
+    ) -> Option<(&'a mut proxy_page::ProxyPage, &'a [SelectableProxy])> {
+        if !matches!(self.current_page, ProxyTabStatePage::Proxy) {
+            return None;
+        }
+        let group = self.get_current_group(store)?;
+        Some((&mut self.proxy_page, &group.proxies))
     }
     fn get_current_proxy<'a>(&self, group: &'a ProxyGroup) -> Option<&'a SelectableProxy> {
         self.proxy_page
             .get_current_item()
             .map(|index| &group.proxies[index])
     }
-    fn draw_group_item(area: Rect, buf: &mut Buffer, data: &ProxyGroup, is_selected: bool) {
+    fn draw_group_item(
+        area: Rect,
+        buf: &mut Buffer,
+        data: &ProxyGroup,
+        is_selected: bool,
+        matched: &[Vec<usize>],
+        theme: &Theme,
+    ) {
         let mut block = Block::bordered()
-            .title_top({
-                let ty = data.proxy_type.str().on_white().black();
-                if is_selected { ty.on_green() } else { ty }
-            })
             .title_top(
-                Line::from(data.latency.map_or("--".to_string(), |l| format!("{l}ms")))
-                    .right_aligned()
-                    .bold(),
+                search::highlight(data.proxy_type.str(), &matched[1], theme)
+                    .patch_style(theme.badge(is_selected)),
+            )
+            .title_top(
+                Line::styled(
+                    data.latency.map_or("--".to_string(), |l| format!("{l}ms")),
+                    theme.latency(data.latency),
+                )
+                .right_aligned()
+                .bold(),
             )
             .padding(ratatui::widgets::Padding::new(1, 1, 0, 0));
 
         if is_selected {
-            block = block.green();
+            block = block.style(theme.selected());
         }
 
         if let Some(now) = data.now.as_ref() {
             block = block.title_bottom(now.to_owned().italic())
         };
 
-        Paragraph::new(data.name.clone().bold())
+        let inner = block.inner(area);
+        block.render(area, buf);
+        // the preview takes the last line, when the name keeps one
+        let name = match inner.height {
+            0 | 1 => inner,
+            _ => {
+                let [name, preview] = Layout::vertical([
+                    ratatui::layout::Constraint::Fill(1),
+                    ratatui::layout::Constraint::Length(1),
+                ])
+                .areas(inner);
+                GroupPreview::new(data, theme).render(preview, buf);
+                name
+            }
+        };
+        Paragraph::new(search::highlight(&data.name, &matched[0], theme).bold())
             .wrap(Wrap { trim: false })
-            .block(block)
-            .render(area, buf);
+            .render(name, buf);
     }
 
-    fn draw(&mut self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+    fn draw(
+        &mut self,
+        area: ratatui::layout::Rect,
+        buf: &mut ratatui::buffer::Buffer,
+        store: &Store,
+        theme: &Theme,
+    ) {
+        self.panes = None;
+        if !self.split || area.width < SPLIT_MIN_WIDTH {
+            match self.current_page {
+                ProxyTabStatePage::Group => self.draw_groups(area, buf, store, theme),
+                ProxyTabStatePage::Proxy => self.draw_proxies(area, buf, store, theme),
+            }
+            return;
+        }
+
+        let [groups, proxies] = Layout::horizontal([
+            ratatui::layout::Constraint::Percentage(40),
+            ratatui::layout::Constraint::Percentage(60),
+        ])
+        .areas(area);
+        self.panes = Some((groups, proxies));
+        self.draw_groups(groups, buf, store, theme);
+
+        let mut block = Block::bordered().border_style(theme.muted());
+        if let Some(group) = self.get_current_group(store) {
+            block = block.title(format!(" {} ", group.name));
+        }
+        if self.current_page == ProxyTabStatePage::Proxy {
+            block = block.border_style(theme.selected());
+        }
+        let inner = block.inner(proxies);
+        block.render(proxies, buf);
+        self.draw_proxies(inner, buf, store, theme);
+    }
+    fn draw_groups(&mut self, area: Rect, buf: &mut Buffer, store: &Store, theme: &Theme) {
+        let data = store.groups();
+        let filter = &mut self.group_filter;
+        filter.update(data.len(), |i| group_fields(&data[i]));
+        let area = filter.draw_prompt(area, buf, data.len());
+        if self.view != GroupView::Cards {
+            self.group_list
+                .update(data, filter.view(), self.view == GroupView::Tree);
+            self.group_list.draw(
+                area,
+                buf,
+                data,
+                // the groups leading to a match in the tree have none
+                |group| {
+                    filter
+                        .search
+                        .matches(&group_fields(group))
+                        .unwrap_or_else(|| vec![vec![]; 2])
+                },
+                theme,
+            );
+            return;
+        }
+        self.group_page.draw(
+            area,
+            buf,
+            filter.view().len(),
+            theme,
+            |position, rect, buffer, state| {
+                let is_selected = position == state.get_current_item();
+                let data = &data[filter.view()[position]];
+                let matched = filter.search.matches(&group_fields(data)).unwrap();
+                Self::draw_group_item(rect, buffer, data, is_selected, &matched, theme);
+            },
+        );
+    }
+    fn draw_proxies(&mut self, area: Rect, buf: &mut Buffer, store: &Store, theme: &Theme) {
+        let Some(group) = self.get_current_group(store) else {
+            return;
+        };
+        let position = group
+            .proxies
+            .iter()
+            .position(|p| group.now.as_ref().is_some_and(|n| n == &p.name));
+        self.proxy_page
+            .draw(area, buf, &group.proxies, position, None, theme);
+    }
+    fn mouse_event(&mut self, event: MouseEvent, drag: &mut mouse::Drag) -> Option<usize> {
+        // side by side, the pane under the mouse gets the clicks and the wheel
+        let position = Position::new(event.column, event.row);
+        let page = match (self.panes, event.kind) {
+            (
+                Some((groups, proxies)),
+                MouseEventKind::Down(_) | MouseEventKind::ScrollUp | MouseEventKind::ScrollDown,
+            ) if groups.contains(position) || proxies.contains(position) => {
+                match groups.contains(position) {
+                    true => ProxyTabStatePage::Group,
+                    false => ProxyTabStatePage::Proxy,
+                }
+            }
+            _ => self.current_page,
+        };
+        if let MouseEventKind::Down(_) = event.kind {
+            self.current_page = page;
+        }
+        match page {
+            ProxyTabStatePage::Group => match self.view {
+                GroupView::Cards => mouse::handle(&mut *self.group_page, event, drag),
+                GroupView::Table | GroupView::Tree => {
+                    mouse::handle(&mut self.group_list, event, drag)
+                }
+            },
+            ProxyTabStatePage::Proxy => mouse::handle(&mut self.proxy_page, event, drag),
+        }
+    }
+    fn type_key(&mut self, key: crossterm::event::KeyEvent, store: &Store) {
         match self.current_page {
             ProxyTabStatePage::Group => {
-                let data = get_groups_data();
-                self.group_page
-                    .draw(area, buf, data.len(), |index, rect, buffer, state| {
-                        let is_selected = index == state.get_current_item();
-                        let data = &data[index];
-                        Self::draw_group_item(rect, buffer, data, is_selected);
-                    });
+                let groups = store.groups();
+                let position =
+                    self.group_filter
+                        .type_key(key, self.get_current_match(), groups.len(), |i| {
+                            group_fields(&groups[i])
+                        });
+                self.select_match(position, groups);
             }
             ProxyTabStatePage::Proxy => {
-                let group = &get_groups_data()[self.group_page.get_current_item()];
-                let position = group
-                    .proxies
-                    .iter()
-                    .position(|p| group.now.as_ref().is_some_and(|n| n == &p.name));
-                self.proxy_page.draw(area, buf, &group.proxies, position);
+                if let Some(group) = self.get_current_group(store) {
+                    self.proxy_page.type_key(key, &group.proxies);
+                }
             }
         }
     }
-    fn key_event(&mut self, key: crossterm::event::KeyEvent) {
-        use crossterm::event::KeyCode::*;
+    fn action(&mut self, action: Action, count: Option<usize>, store: &mut Store) -> Result<()> {
+        use ProxyTabStatePage::*;
 
         match self.current_page {
-            ProxyTabStatePage::Group => match key.code {
-                Char(' ') | Enter => self.current_page = ProxyTabStatePage::Proxy,
-                Char('h') | Up => self.group_page.previous_item(),
-                Char('j') | Down => self.group_page.next_row(),
-                Char('k') | Left => self.group_page.previous_row(),
-                Char('l') | Right => self.group_page.next_item(),
-                Char('r') => {
-                    if let Some(g) = self.get_current_group() {
-                        latency_test_group(&g.name);
-                        refresh_data();
-                    }
+            Group => {
+                let groups = store.groups();
+                if let Some(position) =
+                    self.group_filter
+                        .action(action, self.get_current_match(), groups.len(), |i| {
+                            group_fields(&groups[i])
+                        })
+                {
+                    self.select_match(position, groups);
+                    return Ok(());
                 }
-                _ => {}
-            },
-            ProxyTabStatePage::Proxy => match key.code {
-                Esc => self.current_page = ProxyTabStatePage::Group,
-                Char(' ') | Enter => {
-                    if let Some((g, p)) = self
-                        .get_current_group()
-                        .and_then(|group| self.get_current_proxy(group).map(|proxy| (group, proxy)))
-                    {
-                        select_proxy(&g.name, &p.name);
-                        refresh_data();
-                    };
-                }
-                Char('j') | Up => self.proxy_page.j(),
-                Char('k') | Down => self.proxy_page.k(),
-                Home => todo!(),
-                End => todo!(),
-                Char('u') if key.modifiers == KeyModifiers::CONTROL => todo!(),
-                PageUp => todo!(),
-                Char('d') if key.modifiers == KeyModifiers::CONTROL => todo!(),
-                PageDown => todo!(),
-                Char('R') => {
-                    if let Some(g) = self.get_current_group() {
-                        latency_test_group(&g.name);
-                        refresh_data();
-                    }
+            }
+            Proxy => {
+                if let Some(group) = self.get_current_group(store)
+                    && self.proxy_page.search_action(action, &group.proxies)
+                {
+                    return Ok(());
                 }
-                Char('r') => {
-                    if let Some(p) = self
-                        .get_current_group()
-                        .and_then(|group| self.get_current_proxy(group))
-                    {
-                        latency_test_proxy(&p.name);
-                        refresh_data();
-                    };
+            }
+        }
+
+        if let Some((motion, count)) = Motion::from_action(action, count) {
+            match self.current_page {
+                Group if self.view == GroupView::Cards => {
+                    motion.apply(&mut *self.group_page, count)
                 }
-                _ => {}
-            },
+                Group => motion.apply(&mut self.group_list, count),
+                Proxy => motion.apply(&mut self.proxy_page, count),
+            }
+            return Ok(());
         }
+
+        match (&self.current_page, action) {
+            (Group, Action::Open) if self.get_current_group(store).is_some() => {
+                self.current_page = Proxy
+            }
+            (_, Action::TestGroup) => {
+                if let Some(name) = self.get_current_group(store).map(|g| g.name.clone()) {
+                    store.client().latency_test_group(&name)?;
+                    store.refresh()?;
+                }
+            }
+            (Proxy, Action::Back) => self.current_page = Group,
+            (Proxy, Action::Select) => {
+                if let Some((g, p)) = self.get_current_group(store).and_then(|group| {
+                    self.get_current_proxy(group)
+                        .map(|proxy| (group.name.clone(), proxy.name.clone()))
+                }) {
+                    store.client().select_proxy(&g, &p)?;
+                    store.refresh()?;
+                };
+            }
+            (Proxy, Action::Sort) => {
+                if let Some(group) = self.get_current_group(store) {
+                    self.proxy_page.cycle_sort(&group.proxies);
+                }
+            }
+            (Proxy, Action::JumpToActive) => {
+                if let Some(group) = self.get_current_group(store)
+                    && let Some(index) = group
+                        .proxies
+                        .iter()
+                        .position(|p| group.now.as_ref().is_some_and(|n| n == &p.name))
+                {
+                    self.proxy_page.jump_to(index, &group.proxies);
+                }
+            }
+            (Proxy, Action::TestProxy) => {
+                if let Some(name) = self
+                    .get_current_group(store)
+                    .and_then(|group| self.get_current_proxy(group))
+                    .map(|p| p.name.clone())
+                {
+                    // a timed out proxy fails the test, but its history changes all the same
+                    let result = store.client().latency_test_proxy(&name);
+                    store.refresh()?;
+                    result?;
+                };
+            }
+            _ => {}
+        }
+        Ok(())
     }
 }
 
 #[derive(Debug)]
 enum ProviderTabState {
     Providers,
+    Detail,
     Proxy,
 }
 #[derive(Debug)]
 pub struct ProviderTab {
     current_page: ProviderTabState,
     provider_page: CardPage,
+    provider_filter: Filtered,
     proxy_page: proxy_page::ProxyPage,
+    /// The providers the bulk actions apply to, all of them when empty.
+    marked: HashSet<String>,
+    /// The last bulk action, kept to show its results on the cards.
+    bulk: Option<Bulk>,
+    /// The providers as the last bulk update started, to tell what it changed.
+    before_bulk: Vec<Provider>,
+    /// The last changes of the proxies of each provider, newest first.
+    diffs: HashMap<String, Vec<ProxyDiff>>,
 }
 impl ProviderTab {
-    fn get_current_provider(&self) -> Option<&Provider> {
-        get_providers_data().get(self.provider_page.get_current_item())
+    fn get_current_provider<'a>(&self, store: &'a Store) -> Option<&'a Provider> {
+        self.provider_filter
+            .get(self.provider_page.get_current_item())
+            .and_then(|index| store.providers().get(index))
+    }
+    fn show_provider(&mut self, index: usize, store: &Store) {
+        self.provider_filter.clear(store.providers().len());
+        self.provider_page.select(index);
+        self.current_page = ProviderTabState::Providers;
+    }
+    fn context(&self) -> Context {
+        match self.current_page {
+            ProviderTabState::Providers => Context::Providers,
+            ProviderTabState::Detail => Context::ProviderDetail,
+            ProviderTabState::Proxy => Context::ProviderProxies,
+        }
+    }
+    fn is_searching(&self) -> bool {
+        match self.current_page {
+            ProviderTabState::Providers => self.provider_filter.search.is_typing(),
+            ProviderTabState::Detail => false,
+            ProviderTabState::Proxy => self.proxy_page.is_searching(),
+        }
+    }
+    fn current_proxies<'a>(
+        &'a mut self,
+        store: &'a Store,
+    ) -> Option<(&'a mut proxy_page::ProxyPage, &'a [SelectableProxy])> {
+        if !matches!(self.current_page, ProviderTabState::Proxy) {
+            return None;
+        }
+        let provider = self.get_current_provider(store)?;
+        Some((&mut self.proxy_page, &provider.proxies))
     }
     fn get_current_proxy<'a>(&self, provider: &'a Provider) -> Option<&'a SelectableProxy> {
         self.proxy_page
             .get_current_item()
             .map(|index| &provider.proxies[index])
     }
-    fn draw_provider_item(area: Rect, buf: &mut Buffer, data: &Provider, is_selected: bool) {
+    /// The name of the selected proxy, on the proxy page.
+    fn current_proxy(&self, store: &Store) -> Option<String> {
+        if !matches!(self.current_page, ProviderTabState::Proxy) {
+            return None;
+        }
+        self.get_current_provider(store)
+            .and_then(|provider| self.get_current_proxy(provider))
+            .map(|proxy| proxy.name.clone())
+    }
+    /// How many changes of the proxies of a provider are kept.
+    const DIFFS_KEPT: usize = 5;
+
+    fn is_busy(&self) -> bool {
+        self.bulk.as_ref().is_some_and(|bulk| !bulk.is_finished())
+    }
+    fn record_diff(&mut self, diff: ProxyDiff) {
+        let diffs = self.diffs.entry(diff.provider.clone()).or_default();
+        diffs.insert(0, diff);
+        diffs.truncate(Self::DIFFS_KEPT);
+    }
+    /// Updates the current provider, returning how its proxies changed.
+    fn update_provider(&mut self, store: &mut Store) -> Result<Option<ProxyDiff>> {
+        if matches!(self.current_page, ProviderTabState::Proxy) {
+            return Ok(None);
+        }
+        let Some(provider) = self.get_current_provider(store) else {
+            return Ok(None);
+        };
+        let (name, before) = (provider.name.clone(), provider.proxies.clone());
+        store.client().update_proxy_provider(&name)?;
+        store.refresh()?;
+        let after = store
+            .providers()
+            .iter()
+            .find(|p| p.name == name)
+            .map_or(&[][..], |p| &p.proxies);
+        let diff = ProxyDiff::new(&name, &before, after, Utc::now());
+        self.record_diff(diff.clone());
+        Ok(Some(diff))
+    }
+    /// The marked providers, or all of them.
+    fn bulk_targets(&self, store: &Store) -> Vec<String> {
+        store
+            .providers()
+            .iter()
+            .map(|p| p.name.clone())
+            .filter(|name| self.marked.is_empty() || self.marked.contains(name))
+            .collect()
+    }
+    /// Drops the marks of the providers that are gone.
+    fn retain_marks(&mut self, store: &Store) {
+        self.marked
+            .retain(|name| store.providers().iter().any(|p| &p.name == name));
+    }
+    /// Runs `operation` on the providers `names`.
+    fn start_bulk(&mut self, operation: Operation, names: Vec<String>, store: &Store) {
+        self.bulk = Some(Bulk::start(operation, store.shared_client(), names));
+        if operation == Operation::Update {
+            self.before_bulk = store.providers().to_vec();
+        }
+    }
+    /// Takes the progress of the bulk action, returning its summary once it is over.
+    fn poll_bulk(&mut self, store: &mut Store) -> Result<Option<String>> {
+        let Some(bulk) = &mut self.bulk else {
+            return Ok(None);
+        };
+        if !bulk.poll() || !bulk.is_finished() {
+            return Ok(None);
+        }
+        store.refresh()?;
+        let summary = bulk.summary();
+        if bulk.operation == Operation::Update {
+            let now = Utc::now();
+            let diffs: Vec<ProxyDiff> = bulk
+                .done()
+                .filter_map(|name| {
+                    let before = self.before_bulk.iter().find(|p| p.name == name)?;
+                    let after = store.providers().iter().find(|p| p.name == name)?;
+                    Some(ProxyDiff::new(name, &before.proxies, &after.proxies, now))
+                })
+                .collect();
+            for diff in diffs {
+                self.record_diff(diff);
+            }
+            self.before_bulk.clear();
+        }
+        Ok(Some(summary))
+    }
+    /// The mark and the bulk progress of `provider`, at the bottom of its card.
+    fn card_status(&self, provider: &str, theme: &Theme) -> Line<'static> {
+        let mut line = Line::default();
+        if self.marked.contains(provider) {
+            line.push_span(Span::styled("● ", Style::new().fg(theme.accent)));
+        }
+        if let Some(bulk) = &self.bulk
+            && let Some(progress) = bulk.progress(provider)
+        {
+            line.push_span(match progress {
+                bulk::Progress::Queued => Span::styled("queued", theme.muted()),
+                bulk::Progress::Running => Span::styled(
+                    format!("{}…", bulk.operation.running()),
+                    Style::new().fg(theme.accent),
+                ),
+                bulk::Progress::Done => Span::styled(
+                    format!("✓ {}", bulk.operation.done()),
+                    Style::new().fg(theme.latency_good),
+                ),
+                bulk::Progress::Failed(e) => {
+                    Span::styled(format!("✗ {e}"), Style::new().fg(theme.error))
+                }
+            });
+        }
+        line
+    }
+    fn draw_provider_item(
+        area: Rect,
+        buf: &mut Buffer,
+        data: &Provider,
+        is_selected: bool,
+        matched: &[Vec<usize>],
+        status: Line,
+        theme: &Theme,
+    ) {
         let mut block = Block::bordered()
             .title_top({
-                let ty = format!("{}({})", data.vehicle_type, data.proxies.len())
-                    .on_white()
-                    .black();
-                if is_selected { ty.on_green() } else { ty }
+                let mut ty = search::highlight(&data.vehicle_type, &matched[1], theme);
+                ty.push_span(format!("({})", data.proxies.len()));
+                ty.patch_style(theme.badge(is_selected))
             })
             .title_top(
                 Line::from(
@@ -286,10 +1377,11 @@ impl ProviderTab {
                 .to_string();
                 res.split_whitespace().collect::<Vec<_>>()[0].to_string()
             }))
+            .title_bottom(status.right_aligned())
             .padding(ratatui::widgets::Padding::new(1, 1, 0, 0));
 
         if is_selected {
-            block = block.green();
+            block = block.style(theme.selected());
         }
 
         let inner_area = block.inner(area);
@@ -302,114 +1394,231 @@ impl ProviderTab {
         ])
         .areas(inner_area);
 
-        let ratio = data
-            .subscription_info
-            .as_ref()
-            .filter(|info| info.total.is_some())
-            .map(|info| {
-                (info.upload.unwrap_or(0) + info.download.unwrap_or(0)) as f64
-                    / info.total.unwrap() as f64
-            })
-            .unwrap_or(0.0);
-
-        VerticalGauge::default()
-            .ratio(ratio)
-            .bg(Color::DarkGray)
-            .render(gauge_area, buf);
+        // upload under download, an over-quota subscription being marked
+        let mut gauge = Gauge::vertical()
+            .bg(theme.gauge)
+            .over_limit_style(Style::new().fg(theme.error).bg(theme.gauge).bold());
+        if let Some(info) = &data.subscription_info
+            && let Some(total) = info.total.filter(|&total| total > 0)
+        {
+            gauge = gauge
+                .segment(info.upload.unwrap_or(0) as f64 / total as f64, theme.upload)
+                .segment(
+                    info.download.unwrap_or(0) as f64 / total as f64,
+                    theme.download,
+                );
+        }
+        gauge.render(gauge_area, buf);
 
-        let lines = Text::from(vec![
-            Line::from(data.name.clone().bold()),
-            Line::from(
-                data.subscription_info
-                    .as_ref()
-                    .filter(|info| info.total.is_some())
-                    .map(|info| {
-                        format!(
-                            "{} / {}",
-                            ByteSize::b(info.upload.unwrap_or(0) + info.download.unwrap_or(0))
-                                .display()
-                                .iec_short(),
-                            ByteSize::b(info.total.unwrap()).display().iec_short()
-                        )
+        // the quota takes the last line, when the name keeps one
+        let name_area = match (&data.subscription_info, item_area.height) {
+            (Some(info), 2..) if info.total.is_some() => {
+                let [name_area, usage_area] = Layout::vertical([
+                    ratatui::layout::Constraint::Fill(1),
+                    ratatui::layout::Constraint::Length(1),
+                ])
+                .areas(item_area);
+                let used = info.upload.unwrap_or(0) + info.download.unwrap_or(0);
+                let total = info.total.unwrap_or(0);
+                Gauge::horizontal()
+                    .ratio(match total {
+                        0 => 0.0,
+                        total => used as f64 / total as f64,
                     })
-                    .unwrap_or_default(),
-            ),
-        ]);
+                    .bg(theme.gauge)
+                    .bar_style(theme.latency_good)
+                    .threshold(0.75, theme.latency_ok)
+                    .threshold(0.9, theme.error)
+                    .over_limit_style(Style::new().fg(theme.error).bg(theme.gauge).bold())
+                    .label(format!(
+                        "{} / {}",
+                        ByteSize::b(used).display().iec_short(),
+                        ByteSize::b(total).display().iec_short()
+                    ))
+                    .render(usage_area, buf);
+                name_area
+            }
+            _ => item_area,
+        };
 
-        Paragraph::new(lines)
+        Paragraph::new(search::highlight(&data.name, &matched[0], theme).bold())
             .wrap(Wrap { trim: false })
-            .render(item_area, buf);
+            .render(name_area, buf);
     }
 
-    fn draw(&mut self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+    fn draw(
+        &mut self,
+        area: ratatui::layout::Rect,
+        buf: &mut ratatui::buffer::Buffer,
+        store: &Store,
+        usage: &UsageHistory,
+        theme: &Theme,
+    ) {
         use ProviderTabState::*;
         match self.current_page {
             Providers => {
-                let data = get_providers_data();
-                self.provider_page
-                    .draw(area, buf, data.len(), |index, rect, buffer, state| {
-                        let is_selected = index == state.get_current_item();
+                let data = store.providers();
+                let statuses: Vec<Line> = data
+                    .iter()
+                    .map(|provider| self.card_status(&provider.name, theme))
+                    .collect();
+                let filter = &mut self.provider_filter;
+                filter.update(data.len(), |i| provider_fields(&data[i]));
+                let area = filter.draw_prompt(area, buf, data.len());
+                self.provider_page.draw(
+                    area,
+                    buf,
+                    filter.view().len(),
+                    theme,
+                    |position, rect, buffer, state| {
+                        let is_selected = position == state.get_current_item();
+                        let index = filter.view()[position];
                         let data = &data[index];
-                        Self::draw_provider_item(rect, buffer, data, is_selected);
-                    });
+                        let matched = filter.search.matches(&provider_fields(data)).unwrap();
+                        Self::draw_provider_item(
+                            rect,
+                            buffer,
+                            data,
+                            is_selected,
+                            &matched,
+                            statuses[index].clone(),
+                            theme,
+                        );
+                    },
+                );
+            }
+            Detail => {
+                let Some(provider) = self.get_current_provider(store) else {
+                    return;
+                };
+                let now = Utc::now();
+                let forecast = provider
+                    .subscription_info
+                    .as_ref()
+                    .map(|info| Forecast::new(info, usage.samples(&provider.name), now));
+                let diffs = self
+                    .diffs
+                    .get(&provider.name)
+                    .map_or(&[][..], Vec::as_slice);
+                provider_detail::draw(area, buf, provider, forecast.as_ref(), diffs, now, theme);
             }
             Proxy => {
+                let Some(provider) = self.get_current_provider(store) else {
+                    return;
+                };
                 self.proxy_page.draw(
                     area,
                     buf,
-                    &get_providers_data()[self.provider_page.get_current_item()].proxies,
+                    &provider.proxies,
                     None,
+                    Some(store.used_by()),
+                    theme,
                 );
             }
         }
     }
-    fn key_event(&mut self, key: crossterm::event::KeyEvent) {
+    fn mouse_event(&mut self, event: MouseEvent, drag: &mut mouse::Drag) -> Option<usize> {
+        match self.current_page {
+            ProviderTabState::Providers => mouse::handle(&mut *self.provider_page, event, drag),
+            ProviderTabState::Detail => None,
+            ProviderTabState::Proxy => mouse::handle(&mut self.proxy_page, event, drag),
+        }
+    }
+    fn type_key(&mut self, key: crossterm::event::KeyEvent, store: &Store) {
+        match self.current_page {
+            ProviderTabState::Providers => {
+                let providers = store.providers();
+                let position = self.provider_filter.type_key(
+                    key,
+                    self.provider_page.get_current_item(),
+                    providers.len(),
+                    |i| provider_fields(&providers[i]),
+                );
+                self.provider_page.select(position);
+            }
+            ProviderTabState::Detail => {}
+            ProviderTabState::Proxy => {
+                if let Some(provider) = self.get_current_provider(store) {
+                    self.proxy_page.type_key(key, &provider.proxies);
+                }
+            }
+        }
+    }
+    fn action(&mut self, action: Action, count: Option<usize>, store: &mut Store) -> Result<()> {
         use ProviderTabState::*;
-        use crossterm::event::KeyCode::*;
 
         match self.current_page {
-            Providers => match key.code {
-                Char(' ') | Enter => self.current_page = Proxy,
-                Char('h') | Up => self.provider_page.previous_item(),
-                Char('j') | Down => self.provider_page.next_row(),
-                Char('k') | Left => self.provider_page.previous_row(),
-                Char('l') | Right => self.provider_page.next_item(),
-                Char('f') => {
-                    if let Some(p) = self.get_current_provider() {
-                        update_proxy_provider(&p.name);
-                        refresh_data();
-                    }
+            Providers => {
+                let providers = store.providers();
+                if let Some(position) = self.provider_filter.action(
+                    action,
+                    self.provider_page.get_current_item(),
+                    providers.len(),
+                    |i| provider_fields(&providers[i]),
+                ) {
+                    self.provider_page.select(position);
+                    return Ok(());
                 }
-                _ => {}
-            },
-            Proxy => match key.code {
-                Esc => self.current_page = Providers,
-                Char('j') | Up => self.proxy_page.j(),
-                Char('k') | Down => self.proxy_page.k(),
-                Char('R') => {
-                    if let Some(p) = self.get_current_provider() {
-                        latency_test_provider(&p.name);
-                        refresh_data();
-                    }
+            }
+            Detail => {}
+            Proxy => {
+                if let Some(provider) = self.get_current_provider(store)
+                    && self.proxy_page.search_action(action, &provider.proxies)
+                {
+                    return Ok(());
                 }
-                Char('r') => {
-                    self.proxy_page.get_current_item();
-                    if let Some(p) = self
-                        .get_current_provider()
-                        .and_then(|provider| self.get_current_proxy(provider))
-                    {
-                        latency_test_proxy(&p.name);
-                        refresh_data();
-                    };
-                }
-                Home => todo!(),
-                End => todo!(),
-                Char('u') if key.modifiers == KeyModifiers::CONTROL => todo!(),
-                PageUp => todo!(),
-                Char('d') if key.modifiers == KeyModifiers::CONTROL => todo!(),
-                PageDown => todo!(),
-                _ => {}
-            },
+            }
+        }
+
+        if let Some((motion, count)) = Motion::from_action(action, count) {
+            match self.current_page {
+                Providers => motion.apply(&mut *self.provider_page, count),
+                Detail => {}
+                Proxy => motion.apply(&mut self.proxy_page, count),
+            }
+            return Ok(());
+        }
+
+        match (&self.current_page, action) {
+            (Providers | Detail, Action::Open) if self.get_current_provider(store).is_some() => {
+                self.current_page = Proxy
+            }
+            (Providers, Action::Details) if self.get_current_provider(store).is_some() => {
+                self.current_page = Detail
+            }
+            (Detail, Action::Back) => self.current_page = Providers,
+            (Providers, Action::Mark) => {
+                if let Some(name) = self.get_current_provider(store).map(|p| p.name.clone())
+                    && !self.marked.remove(&name)
+                {
+                    self.marked.insert(name);
+                }
+            }
+            (Proxy, Action::Back) => self.current_page = Providers,
+            (Proxy, Action::Sort) => {
+                if let Some(provider) = self.get_current_provider(store) {
+                    self.proxy_page.cycle_sort(&provider.proxies);
+                }
+            }
+            (Proxy, Action::HealthcheckProvider) => {
+                if let Some(name) = self.get_current_provider(store).map(|p| p.name.clone()) {
+                    store.client().latency_test_provider(&name)?;
+                    store.refresh()?;
+                }
+            }
+            (Proxy, Action::TestProxy) => {
+                if let Some(name) = self
+                    .get_current_provider(store)
+                    .and_then(|provider| self.get_current_proxy(provider))
+                    .map(|p| p.name.clone())
+                {
+                    let result = store.client().latency_test_proxy(&name);
+                    store.refresh()?;
+                    result?;
+                };
+            }
+            _ => {}
         }
+        Ok(())
     }
 }
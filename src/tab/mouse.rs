@@ -0,0 +1,197 @@
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+
+use super::navigation::{Motion, Navigable};
+
+/// A page whose items and scrollbar can be clicked, as they were last drawn.
+pub trait Clickable: Navigable {
+    /// The position of the item drawn at `position`.
+    fn item_at(&self, position: Position) -> Option<usize>;
+    /// Where the scrollbar was drawn, arrows included.
+    fn scrollbar(&self) -> Rect;
+}
+
+/// The state of a mouse press carried over to the next events.
+#[derive(Debug, Default)]
+pub struct Drag {
+    /// Whether the press started on the scrollbar, which then follows the mouse.
+    on_scrollbar: bool,
+}
+
+/// Moves the selection of `target` with a wheel, a click or a drag of the
+/// scrollbar, returning the position of the item clicked.
+pub fn handle<T: Clickable>(target: &mut T, event: MouseEvent, drag: &mut Drag) -> Option<usize> {
+    let position = Position::new(event.column, event.row);
+    // a wheel step scrolls a row of cards, or a few rows of a table
+    let wheel = if T::GRID { 1 } else { 3 };
+    match event.kind {
+        MouseEventKind::ScrollDown => Motion::Down.apply(target, wheel),
+        MouseEventKind::ScrollUp => Motion::Up.apply(target, wheel),
+        MouseEventKind::Down(MouseButton::Left) if target.scrollbar().contains(position) => {
+            let area = target.scrollbar();
+            // the arrows move a row, the track follows the mouse
+            if event.row == area.top() {
+                Motion::Up.apply(target, 1);
+            } else if event.row == area.bottom() - 1 {
+                Motion::Down.apply(target, 1);
+            } else {
+                drag.on_scrollbar = true;
+                scroll_to(target, event.row);
+            }
+        }
+        MouseEventKind::Down(MouseButton::Left) => {
+            let clicked = target.item_at(position)?;
+            target.select(clicked);
+            return Some(clicked);
+        }
+        MouseEventKind::Drag(MouseButton::Left) if drag.on_scrollbar => {
+            scroll_to(target, event.row)
+        }
+        MouseEventKind::Up(MouseButton::Left) => drag.on_scrollbar = false,
+        _ => {}
+    }
+    None
+}
+
+/// Selects the row under `row` on the scrollbar track, keeping the column of a grid.
+fn scroll_to<T: Clickable>(target: &mut T, row: u16) {
+    let area = target.scrollbar();
+    if area.height < 3 {
+        return;
+    }
+    let (top, bottom) = (area.top(), area.bottom() - 1);
+
+    let row_len = target.row_len().max(1);
+    let rows = target.len().div_ceil(row_len);
+    // the track between the arrows
+    let track = (bottom - top - 1) as usize;
+    let offset = (row.clamp(top + 1, bottom - 1) - top - 1) as usize;
+    let target_row = match track {
+        1 => 0,
+        _ => offset * rows.saturating_sub(1) / (track - 1),
+    };
+    let column = target.selected() % row_len;
+    Motion::Nth(target_row * row_len + column).apply(target, 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+
+    use super::*;
+
+    /// 100 rows of a table whose rows are drawn from y = 2, next to a
+    /// scrollbar at x = 50.
+    #[derive(Default)]
+    struct Table(usize);
+
+    impl Navigable for Table {
+        const GRID: bool = false;
+
+        fn len(&self) -> usize {
+            100
+        }
+        fn selected(&self) -> usize {
+            self.0
+        }
+        fn select(&mut self, position: usize) {
+            self.0 = position;
+        }
+        fn row_len(&self) -> usize {
+            1
+        }
+        fn page_len(&self) -> usize {
+            10
+        }
+    }
+
+    impl Clickable for Table {
+        fn item_at(&self, position: Position) -> Option<usize> {
+            (position.x < 50 && (2..12).contains(&position.y)).then(|| position.y as usize - 2)
+        }
+        fn scrollbar(&self) -> Rect {
+            Rect::new(50, 0, 1, 12)
+        }
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn test_click_and_wheel() {
+        let mut table = Table::default();
+        let mut drag = Drag::default();
+        let left = MouseEventKind::Down(MouseButton::Left);
+        assert_eq!(handle(&mut table, mouse(left, 10, 5), &mut drag), Some(3));
+        assert_eq!(table.0, 3);
+        assert_eq!(handle(&mut table, mouse(left, 10, 1), &mut drag), None);
+        assert_eq!(table.0, 3);
+
+        handle(
+            &mut table,
+            mouse(MouseEventKind::ScrollDown, 0, 0),
+            &mut drag,
+        );
+        assert_eq!(table.0, 6);
+        handle(&mut table, mouse(MouseEventKind::ScrollUp, 0, 0), &mut drag);
+        handle(&mut table, mouse(MouseEventKind::ScrollUp, 0, 0), &mut drag);
+        assert_eq!(table.0, 0);
+    }
+
+    #[test]
+    fn test_drag_scrollbar() {
+        let mut table = Table::default();
+        let mut drag = Drag::default();
+        let left = MouseButton::Left;
+        // the arrows move a row
+        handle(
+            &mut table,
+            mouse(MouseEventKind::Down(left), 50, 11),
+            &mut drag,
+        );
+        assert_eq!(table.0, 1);
+        handle(
+            &mut table,
+            mouse(MouseEventKind::Down(left), 50, 0),
+            &mut drag,
+        );
+        assert_eq!(table.0, 0);
+
+        // the track is rows 1 to 10
+        handle(
+            &mut table,
+            mouse(MouseEventKind::Down(left), 50, 10),
+            &mut drag,
+        );
+        assert_eq!(table.0, 99);
+        handle(
+            &mut table,
+            mouse(MouseEventKind::Drag(left), 20, 4),
+            &mut drag,
+        );
+        assert_eq!(table.0, 33);
+        handle(
+            &mut table,
+            mouse(MouseEventKind::Drag(left), 20, 40),
+            &mut drag,
+        );
+        assert_eq!(table.0, 99);
+        handle(
+            &mut table,
+            mouse(MouseEventKind::Up(left), 20, 40),
+            &mut drag,
+        );
+        handle(
+            &mut table,
+            mouse(MouseEventKind::Drag(left), 50, 1),
+            &mut drag,
+        );
+        assert_eq!(table.0, 99);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::keymap::Action;
+
+/// A list whose selection can be moved, e.g. a card page or the proxy table.
+pub trait Navigable {
+    /// Whether items are laid out in rows of [`Navigable::row_len`] items, which
+    /// makes left and right move within a row.
+    const GRID: bool;
+
+    fn len(&self) -> usize;
+    fn selected(&self) -> usize;
+    fn select(&mut self, position: usize);
+    /// The number of items in a row.
+    fn row_len(&self) -> usize;
+    /// The number of items shown at once.
+    fn page_len(&self) -> usize;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    Up,
+    Down,
+    Left,
+    Right,
+    HalfPageUp,
+    HalfPageDown,
+    PageUp,
+    PageDown,
+    First,
+    Last,
+    /// The item at a position, as in `5G`.
+    Nth(usize),
+}
+
+impl Motion {
+    /// The motion of `action` with its count, `count` going to the numbered
+    /// item for [`Action::First`] and [`Action::Last`] as in `5G`.
+    pub fn from_action(action: Action, count: Option<usize>) -> Option<(Self, usize)> {
+        let motion = match action {
+            Action::Up => Motion::Up,
+            Action::Down => Motion::Down,
+            Action::Left => Motion::Left,
+            Action::Right => Motion::Right,
+            Action::HalfPageUp => Motion::HalfPageUp,
+            Action::HalfPageDown => Motion::HalfPageDown,
+            Action::PageUp => Motion::PageUp,
+            Action::PageDown => Motion::PageDown,
+            Action::First | Action::Last => {
+                let motion = match (action, count) {
+                    (_, Some(n)) => Motion::Nth(n.saturating_sub(1)),
+                    (Action::First, None) => Motion::First,
+                    _ => Motion::Last,
+                };
+                return Some((motion, 1));
+            }
+            _ => return None,
+        };
+        Some((motion, count.unwrap_or(1)))
+    }
+
+    /// Moves the selection of `target` `count` times.
+    pub fn apply<T: Navigable>(self, target: &mut T, count: usize) {
+        let len = target.len();
+        if len == 0 {
+            return;
+        }
+        let current = target.selected().min(len - 1);
+        let row = target.row_len().max(1);
+        let page = (target.page_len() / row).max(1) * row;
+        let half_page = (page / row / 2).max(1) * row;
+        let forward = |n: usize| current.saturating_add(n.saturating_mul(count)).min(len - 1);
+        let backward = |n: usize| current.saturating_sub(n.saturating_mul(count));
+
+        let position = match self {
+            Motion::Down => forward(row),
+            Motion::Up => backward(row),
+            Motion::Right if T::GRID => forward(1),
+            Motion::Left if T::GRID => backward(1),
+            Motion::Right | Motion::Left => current,
+            Motion::HalfPageDown => forward(half_page),
+            Motion::HalfPageUp => backward(half_page),
+            Motion::PageDown => forward(page),
+            Motion::PageUp => backward(page),
+            Motion::First => 0,
+            Motion::Last => len - 1,
+            Motion::Nth(n) => n.min(len - 1),
+        };
+        target.select(position);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 10 items in rows of 3, 2 rows at once.
+    #[derive(Default)]
+    struct Grid(usize);
+
+    impl Navigable for Grid {
+        const GRID: bool = true;
+
+        fn len(&self) -> usize {
+            10
+        }
+        fn selected(&self) -> usize {
+            self.0
+        }
+        fn select(&mut self, position: usize) {
+            self.0 = position;
+        }
+        fn row_len(&self) -> usize {
+            3
+        }
+        fn page_len(&self) -> usize {
+            6
+        }
+    }
+
+    /// 100 items, 20 rows at once.
+    #[derive(Default)]
+    struct List(usize);
+
+    impl Navigable for List {
+        const GRID: bool = false;
+
+        fn len(&self) -> usize {
+            100
+        }
+        fn selected(&self) -> usize {
+            self.0
+        }
+        fn select(&mut self, position: usize) {
+            self.0 = position;
+        }
+        fn row_len(&self) -> usize {
+            1
+        }
+        fn page_len(&self) -> usize {
+            20
+        }
+    }
+
+    #[test]
+    fn test_grid_motions() {
+        let mut grid = Grid::default();
+        Motion::Right.apply(&mut grid, 1);
+        assert_eq!(grid.0, 1);
+        Motion::Down.apply(&mut grid, 1);
+        assert_eq!(grid.0, 4);
+        Motion::Down.apply(&mut grid, 5);
+        assert_eq!(grid.0, 9);
+        Motion::Up.apply(&mut grid, 1);
+        assert_eq!(grid.0, 6);
+        Motion::HalfPageUp.apply(&mut grid, 1);
+        assert_eq!(grid.0, 3);
+        Motion::PageUp.apply(&mut grid, 1);
+        assert_eq!(grid.0, 0);
+        Motion::PageDown.apply(&mut grid, 1);
+        assert_eq!(grid.0, 6);
+        Motion::Left.apply(&mut grid, 10);
+        assert_eq!(grid.0, 0);
+        Motion::Last.apply(&mut grid, 1);
+        assert_eq!(grid.0, 9);
+    }
+
+    #[test]
+    fn test_list_motions() {
+        let mut list = List::default();
+        Motion::Right.apply(&mut list, 1);
+        assert_eq!(list.0, 0);
+        Motion::HalfPageDown.apply(&mut list, 1);
+        assert_eq!(list.0, 10);
+        Motion::PageDown.apply(&mut list, 2);
+        assert_eq!(list.0, 50);
+        Motion::PageDown.apply(&mut list, 3);
+        assert_eq!(list.0, 99);
+        Motion::Nth(1000).apply(&mut list, 1);
+        assert_eq!(list.0, 99);
+        Motion::First.apply(&mut list, 1);
+        assert_eq!(list.0, 0);
+    }
+
+    #[test]
+    fn test_from_action() {
+        assert_eq!(
+            Motion::from_action(Action::Down, Some(5)),
+            Some((Motion::Down, 5))
+        );
+        assert_eq!(
+            Motion::from_action(Action::PageUp, None),
+            Some((Motion::PageUp, 1))
+        );
+        assert_eq!(
+            Motion::from_action(Action::First, None),
+            Some((Motion::First, 1))
+        );
+        assert_eq!(
+            Motion::from_action(Action::Last, Some(42)),
+            Some((Motion::Nth(41), 1))
+        );
+        assert_eq!(
+            Motion::from_action(Action::First, Some(7)),
+            Some((Motion::Nth(6), 1))
+        );
+        assert_eq!(Motion::from_action(Action::Sort, None), None);
+    }
+}
@@ -0,0 +1,180 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Layout, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Widget},
+};
+
+use super::{
+    help, popup,
+    search::{self, Filtered},
+};
+use crate::{
+    backend::Store,
+    keymap::{Action, Context, Keymap},
+    theme::Theme,
+};
+
+/// Where a palette entry leads, as indices into the [`Store`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Group(usize),
+    Proxy { group: usize, proxy: usize },
+    Provider(usize),
+}
+
+#[derive(Debug)]
+struct Entry {
+    target: Target,
+    kind: &'static str,
+    name: String,
+    /// The group of a proxy.
+    parent: String,
+}
+
+impl Entry {
+    fn fields(&self) -> Vec<&str> {
+        vec![&self.name, &self.parent, self.kind]
+    }
+}
+
+/// A popup searching every group, every proxy of every group and every provider.
+#[derive(Debug)]
+pub struct Palette {
+    entries: Vec<Entry>,
+    filter: Filtered,
+    selected: usize,
+    offset: usize,
+}
+
+impl Palette {
+    pub fn new(store: &Store) -> Self {
+        let groups = store.groups().iter().enumerate().map(|(i, g)| Entry {
+            target: Target::Group(i),
+            kind: "group",
+            name: g.name.clone(),
+            parent: String::new(),
+        });
+        let providers = store.providers().iter().enumerate().map(|(i, p)| Entry {
+            target: Target::Provider(i),
+            kind: "provider",
+            name: p.name.clone(),
+            parent: String::new(),
+        });
+        let proxies = store.groups().iter().enumerate().flat_map(|(group, g)| {
+            g.proxies.iter().enumerate().map(move |(proxy, p)| Entry {
+                target: Target::Proxy { group, proxy },
+                kind: "proxy",
+                name: p.name.clone(),
+                parent: g.name.clone(),
+            })
+        });
+        let entries: Vec<Entry> = groups.chain(providers).chain(proxies).collect();
+
+        let mut filter = Filtered::default();
+        filter.clear(entries.len());
+        filter.search.start();
+        Self {
+            entries,
+            filter,
+            selected: 0,
+            offset: 0,
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        let matches = self.filter.view().len();
+        self.selected = (self.selected + 1).min(matches.saturating_sub(1));
+    }
+
+    /// Where the selected entry leads, `None` when nothing matches.
+    pub fn chosen(&self) -> Option<Target> {
+        let index = self.filter.get(self.selected)?;
+        Some(self.entries[index].target)
+    }
+
+    /// Edits the query with a key bound to nothing in the palette.
+    pub fn type_key(&mut self, key: KeyEvent) {
+        let entries = &self.entries;
+        self.selected = self
+            .filter
+            .type_key(key, self.selected, entries.len(), |i| entries[i].fields());
+    }
+
+    /// The hints while the palette is open.
+    pub fn hints(&self, keymap: &Keymap, theme: &Theme) -> Line<'static> {
+        let keys = |action| help::keys(keymap, Context::Palette, action);
+        let select = [Action::Down, Action::Up]
+            .into_iter()
+            .filter_map(keys)
+            .collect::<Vec<_>>()
+            .join("/");
+        let hints = [
+            ((!select.is_empty()).then_some(select), "select"),
+            (keys(Action::Open), "go to"),
+            (keys(Action::Back), "close"),
+        ];
+        help::line(
+            hints
+                .into_iter()
+                .filter_map(|(keys, description)| Some((keys?, description))),
+            theme,
+        )
+    }
+
+    pub fn draw(&mut self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let popup = popup::centered(area, buf, 72, 20);
+        let block = Block::bordered()
+            .title(" Go to ")
+            .border_style(Style::new().fg(theme.accent));
+        let inner = block.inner(popup);
+        block.render(popup, buf);
+
+        let [prompt, list] = Layout::vertical([
+            ratatui::layout::Constraint::Length(1),
+            ratatui::layout::Constraint::Fill(1),
+        ])
+        .areas(inner);
+        self.filter.draw_prompt(prompt, buf, self.entries.len());
+
+        // keep the selection in sight
+        let height = list.height as usize;
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if height > 0 && self.selected >= self.offset + height {
+            self.offset = self.selected + 1 - height;
+        }
+
+        for (row, &index) in self
+            .filter
+            .view()
+            .iter()
+            .enumerate()
+            .skip(self.offset)
+            .take(height)
+        {
+            let entry = &self.entries[index];
+            let matched = self.filter.search.matches(&entry.fields()).unwrap();
+            let mut line = Line::from(Span::styled(format!("{:<9}", entry.kind), theme.muted()));
+            line.extend(search::highlight(&entry.name, &matched[0], theme));
+            if !entry.parent.is_empty() {
+                line.push_span(Span::styled("  in ", theme.muted()));
+                for span in search::highlight(&entry.parent, &matched[1], theme) {
+                    let style = theme.muted().patch(span.style);
+                    line.push_span(span.style(style));
+                }
+            }
+            if row == self.selected {
+                line = line.reversed();
+            }
+            let y = list.y + (row - self.offset) as u16;
+            line.render(Rect::new(list.x, y, list.width, 1), buf);
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Layout, Rect},
+    style::Style,
+    text::Line,
+    widgets::{Block, Clear, Paragraph, Widget, Wrap},
+};
+
+use crate::theme::Theme;
+
+/// Clears a `width` x `height` rect in the middle of `area`, shrunk to fit.
+pub fn centered(area: Rect, buf: &mut Buffer, width: u16, height: u16) -> Rect {
+    let [_, column, _] = Layout::horizontal([
+        ratatui::layout::Constraint::Fill(1),
+        ratatui::layout::Constraint::Length(width.min(area.width)),
+        ratatui::layout::Constraint::Fill(1),
+    ])
+    .areas(area);
+    let [_, popup, _] = Layout::vertical([
+        ratatui::layout::Constraint::Fill(1),
+        ratatui::layout::Constraint::Length(height.min(area.height)),
+        ratatui::layout::Constraint::Fill(1),
+    ])
+    .areas(column);
+    Clear.render(popup, buf);
+    popup
+}
+
+/// A yes/no question in the middle of `area`.
+pub fn draw_question(area: Rect, buf: &mut Buffer, question: &str, theme: &Theme) {
+    let width = (question.chars().count() as u16 + 4).clamp(30, 60);
+    let popup = centered(area, buf, width, 5);
+    Paragraph::new(vec![
+        Line::from(question.to_string()),
+        Line::styled("y/enter: yes  other keys: no", theme.muted()),
+    ])
+    .wrap(Wrap { trim: false })
+    .block(Block::bordered().border_style(Style::new().fg(theme.highlight)))
+    .render(popup, buf);
+}
@@ -0,0 +1,174 @@
+use bytesize::ByteSize;
+use chrono::{DateTime, Local, Utc};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Widget},
+};
+
+use super::{gauge::Gauge, proxy_diff::ProxyDiff};
+use crate::{backend::Provider, theme::Theme, usage::Forecast};
+
+fn size(bytes: u64) -> String {
+    ByteSize::b(bytes).display().iec_short().to_string()
+}
+
+fn date(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local).format("%Y-%m-%d").to_string()
+}
+
+/// A line of the page: a name in a column, then `value`.
+fn field<'a>(name: &'a str, value: impl Into<Span<'a>>) -> Line<'a> {
+    Line::from(vec![Span::raw(format!("{name:<12}")).bold(), value.into()])
+}
+
+/// The last updates, newest first, with the proxies they added and removed.
+fn updates(diffs: &[ProxyDiff], theme: &Theme) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::default(), Line::from("Last updates").bold()];
+    if diffs.is_empty() {
+        lines.push(Line::styled("none since clashi started", theme.muted()));
+    }
+    for diff in diffs {
+        let mut line = Line::from(format!("{}  ", diff.local_time()));
+        line.extend(diff.summary(theme));
+        lines.push(line);
+        for (names, sign, color) in [
+            (&diff.added, "+ ", theme.latency_good),
+            (&diff.removed, "- ", theme.error),
+        ] {
+            if !names.is_empty() {
+                lines.push(Line::styled(
+                    format!("  {sign}{}", names.join(", ")),
+                    Style::new().fg(color),
+                ));
+            }
+        }
+    }
+    lines
+}
+
+/// The subscription of `provider`: its traffic, quota and expiry, and when the
+/// quota runs out at the current rate. Then how its last updates changed its proxies.
+pub fn draw(
+    area: Rect,
+    buf: &mut Buffer,
+    provider: &Provider,
+    forecast: Option<&Forecast>,
+    diffs: &[ProxyDiff],
+    now: DateTime<Utc>,
+    theme: &Theme,
+) {
+    let block = Block::bordered()
+        .title(format!(" {} ", provider.name))
+        .border_style(theme.selected())
+        .padding(ratatui::widgets::Padding::new(1, 1, 0, 0));
+    let inner = block.inner(area);
+    block.render(area, buf);
+
+    let mut lines = vec![
+        Line::from(format!(
+            "{}, {} proxies",
+            provider.vehicle_type,
+            provider.proxies.len()
+        ))
+        .style(theme.muted()),
+        Line::default(),
+    ];
+    let Some(forecast) = forecast else {
+        lines.push(Line::from("No subscription information").bold());
+        lines.extend(updates(diffs, theme));
+        Paragraph::new(lines).render(inner, buf);
+        return;
+    };
+
+    let error = Style::new().fg(theme.error);
+    lines.push(field("Upload", size(forecast.upload)));
+    lines.push(field("Download", size(forecast.download)));
+    let gauge_row = match forecast.total {
+        Some(total) => {
+            lines.push(field(
+                "Used",
+                format!(
+                    "{} / {} ({:.0}%)",
+                    size(forecast.used),
+                    size(total),
+                    forecast.used as f64 * 100.0 / total.max(1) as f64
+                ),
+            ));
+            lines.push(Line::default());
+            let row = lines.len() - 1;
+            let remaining = forecast.remaining().unwrap_or(0);
+            lines.push(field(
+                "Remaining",
+                match forecast.used.checked_sub(total) {
+                    Some(over) if over > 0 => {
+                        Span::styled(format!("none, {} over the quota", size(over)), error)
+                    }
+                    _ => Span::raw(size(remaining)),
+                },
+            ));
+            Some(row)
+        }
+        None => {
+            lines.push(field("Used", size(forecast.used)));
+            None
+        }
+    };
+
+    lines.push(field(
+        "Expires",
+        match (forecast.expire, forecast.days_to_expiry(now)) {
+            (Some(expire), Some(days)) if days >= 0 => {
+                Span::raw(format!("{}, in {days} days", date(expire)))
+            }
+            (Some(expire), Some(days)) => {
+                Span::styled(format!("{}, {} days ago", date(expire), -days), error)
+            }
+            _ => Span::raw("never"),
+        },
+    ));
+    lines.push(field(
+        "Average",
+        match forecast.daily {
+            Some(daily) => Span::raw(format!(
+                "{} a day over {:.1} days",
+                size(daily as u64),
+                forecast.days
+            )),
+            None => Span::styled("not enough samples yet", theme.muted()),
+        },
+    ));
+    if forecast.total.is_some() {
+        lines.push(field(
+            "Runs out",
+            match forecast.exhaustion {
+                Some(exhaustion) if forecast.exhausted_before_expiry() => Span::styled(
+                    format!("{} at this rate, before it expires", date(exhaustion)),
+                    error.bold(),
+                ),
+                Some(exhaustion) => Span::raw(format!("{} at this rate", date(exhaustion))),
+                None => Span::styled("unknown", theme.muted()),
+            },
+        ));
+    }
+    lines.extend(updates(diffs, theme));
+    Paragraph::new(lines).render(inner, buf);
+
+    if let (Some(row), Some(total)) = (gauge_row, forecast.total.filter(|&total| total > 0)) {
+        let area = Rect {
+            y: inner.y + row as u16,
+            height: 1,
+            width: inner.width.min(60),
+            ..inner
+        }
+        .intersection(inner);
+        Gauge::horizontal()
+            .bg(theme.gauge)
+            .segment(forecast.upload as f64 / total as f64, theme.upload)
+            .segment(forecast.download as f64 / total as f64, theme.download)
+            .over_limit_style(error.bg(theme.gauge).bold())
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,175 @@
+use chrono::{DateTime, Local, Utc};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Widget},
+};
+
+use super::popup;
+use crate::{backend::SelectableProxy, theme::Theme};
+
+/// How the proxies of a provider changed with an update.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyDiff {
+    pub provider: String,
+    pub time: DateTime<Utc>,
+    /// In the order of the new proxies.
+    pub added: Vec<String>,
+    /// In the order of the old proxies.
+    pub removed: Vec<String>,
+    pub kept: Vec<String>,
+}
+
+impl ProxyDiff {
+    pub fn new(
+        provider: &str,
+        before: &[SelectableProxy],
+        after: &[SelectableProxy],
+        time: DateTime<Utc>,
+    ) -> Self {
+        let has = |proxies: &[SelectableProxy], name: &str| proxies.iter().any(|p| p.name == name);
+        let (kept, added) = after
+            .iter()
+            .map(|p| p.name.clone())
+            .partition(|name| has(before, name));
+        Self {
+            provider: provider.to_string(),
+            time,
+            added,
+            removed: before
+                .iter()
+                .filter(|p| !has(after, &p.name))
+                .map(|p| p.name.clone())
+                .collect(),
+            kept,
+        }
+    }
+
+    /// The counts, such as `+2 added  -1 removed  5 kept`.
+    pub fn summary(&self, theme: &Theme) -> Vec<Span<'static>> {
+        vec![
+            Span::styled(
+                format!("+{} added", self.added.len()),
+                Style::new().fg(theme.latency_good),
+            ),
+            Span::raw("  "),
+            Span::styled(
+                format!("-{} removed", self.removed.len()),
+                Style::new().fg(theme.error),
+            ),
+            Span::raw("  "),
+            Span::styled(format!("{} kept", self.kept.len()), theme.muted()),
+        ]
+    }
+
+    /// When the update happened, in the local time.
+    pub fn local_time(&self) -> String {
+        self.time
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    }
+}
+
+/// A popup listing the proxies added, removed and kept by an update.
+#[derive(Debug)]
+pub struct DiffPopup {
+    diff: ProxyDiff,
+    offset: usize,
+}
+
+impl DiffPopup {
+    pub fn new(diff: ProxyDiff) -> Self {
+        Self { diff, offset: 0 }
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.offset = self.offset.saturating_sub(1);
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.offset += 1;
+    }
+
+    fn lines(&self, theme: &Theme) -> Vec<Line<'static>> {
+        let diff = &self.diff;
+        let mut lines = vec![Line::from(diff.summary(theme)), Line::default()];
+        let names = [
+            (&diff.added, "+ ", Style::new().fg(theme.latency_good)),
+            (&diff.removed, "- ", Style::new().fg(theme.error)),
+            (&diff.kept, "  ", theme.muted()),
+        ];
+        for (names, sign, style) in names {
+            lines.extend(
+                names
+                    .iter()
+                    .map(|name| Line::styled(format!("{sign}{name}"), style)),
+            );
+        }
+        lines
+    }
+
+    pub fn draw(&mut self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let lines = self.lines(theme);
+        let popup = popup::centered(area, buf, 48, lines.len() as u16 + 2);
+        // stop scrolling once the last line is in sight
+        let height = popup.height.saturating_sub(2) as usize;
+        self.offset = self.offset.min(lines.len().saturating_sub(height));
+        Paragraph::new(lines)
+            .scroll((self.offset as u16, 0))
+            .block(
+                Block::bordered()
+                    .title(format!(" {} updated ", self.diff.provider))
+                    .title_bottom(Line::from(self.diff.local_time()).right_aligned())
+                    .border_style(Style::new().fg(theme.accent)),
+            )
+            .render(popup, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::ProxyType;
+
+    fn proxies(names: &[&str]) -> Vec<SelectableProxy> {
+        names
+            .iter()
+            .map(|name| SelectableProxy {
+                name: name.to_string(),
+                udp: true,
+                proxy_type: ProxyType::Vmess,
+                latency: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = proxies(&["HK 01", "HK 02", "JP 01"]);
+        let after = proxies(&["JP 02", "HK 01", "JP 01", "SG 01"]);
+        let diff = ProxyDiff::new("A", &before, &after, Utc::now());
+        assert_eq!(diff.added, ["JP 02", "SG 01"]);
+        assert_eq!(diff.removed, ["HK 02"]);
+        assert_eq!(diff.kept, ["HK 01", "JP 01"]);
+
+        let theme = Theme::default();
+        let summary: String = diff
+            .summary(&theme)
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+        assert_eq!(summary, "+2 added  -1 removed  2 kept");
+        let lines: Vec<String> = DiffPopup::new(diff)
+            .lines(&theme)
+            .iter()
+            .map(Line::to_string)
+            .collect();
+        assert_eq!(
+            lines[2..],
+            ["+ JP 02", "+ SG 01", "- HK 02", "  HK 01", "  JP 01"]
+        );
+    }
+}
@@ -1,15 +1,122 @@
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+
+use crossterm::event::KeyEvent;
 use ratatui::{
-    layout::Layout,
+    layout::{Layout, Position, Rect},
     style::{Modifier, Style, Stylize},
-    widgets::{Row, Scrollbar, ScrollbarState, StatefulWidget, Table, TableState},
+    text::{Line, Span},
+    widgets::{Cell, Row, Scrollbar, ScrollbarState, StatefulWidget, Table, TableState},
+};
+
+use super::{
+    mouse::Clickable,
+    navigation::Navigable,
+    search::{self, Filtered},
+    sweep::Sweep,
 };
+use crate::{
+    backend::{ControllerClient, Membership, SelectableProxy},
+    keymap::Action,
+    theme::Theme,
+};
+
+/// The searchable fields of a proxy: name, type and `udp` if it supports UDP.
+fn proxy_fields(proxy: &SelectableProxy) -> Vec<&str> {
+    vec![
+        &proxy.name,
+        proxy.proxy_type.str(),
+        if proxy.udp { "udp" } else { "" },
+    ]
+}
 
-use crate::backend::SelectableProxy;
+/// The order of the proxy table, cycled with `s`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortMode {
+    /// The order of the controller.
+    #[default]
+    Original,
+    /// Fastest first, failed and untested proxies last.
+    Latency,
+    Name,
+    Type,
+    /// Proxies supporting UDP first.
+    Udp,
+}
+
+impl SortMode {
+    fn next(self) -> Self {
+        match self {
+            SortMode::Original => SortMode::Latency,
+            SortMode::Latency => SortMode::Name,
+            SortMode::Name => SortMode::Type,
+            SortMode::Type => SortMode::Udp,
+            SortMode::Udp => SortMode::Original,
+        }
+    }
+
+    /// The sorted column of the table.
+    fn column(self) -> Option<usize> {
+        match self {
+            SortMode::Original => None,
+            SortMode::Name => Some(0),
+            SortMode::Type => Some(1),
+            SortMode::Latency => Some(2),
+            SortMode::Udp => Some(3),
+        }
+    }
+
+    fn compare(self, a: &SelectableProxy, b: &SelectableProxy) -> Ordering {
+        match self {
+            SortMode::Original => Ordering::Equal,
+            // a delay of 0 is a failed test
+            SortMode::Latency => match (a.latency.filter(|&l| l > 0), b.latency.filter(|&l| l > 0))
+            {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+            SortMode::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortMode::Type => a.proxy_type.str().cmp(b.proxy_type.str()),
+            SortMode::Udp => b.udp.cmp(&a.udp),
+        }
+    }
+}
+
+/// The groups of a proxy, those using it in bold.
+fn used_by_line(groups: &[Membership], theme: &Theme) -> Line<'static> {
+    if groups.is_empty() {
+        return Line::styled("--", theme.muted());
+    }
+    let mut line = Line::default();
+    for (i, membership) in groups.iter().enumerate() {
+        if i > 0 {
+            line.push_span(", ");
+        }
+        line.push_span(match membership.active {
+            true => Span::styled(
+                membership.group.clone(),
+                Style::new().fg(theme.highlight).bold(),
+            ),
+            false => Span::raw(membership.group.clone()),
+        });
+    }
+    line
+}
 
 #[derive(Debug)]
 pub struct ProxyPage {
     state: TableState,
     scroll_state: ScrollbarState,
+    filter: Filtered,
+    sort: SortMode,
+    /// The number of rows the table showed when it was last drawn.
+    page_len: usize,
+    /// Where the rows and the scrollbar were last drawn.
+    rows: Rect,
+    scrollbar: Rect,
+    /// The latency sweep running on the proxies, its results replacing theirs.
+    sweep: Option<Sweep>,
 }
 
 impl ProxyPage {
@@ -17,20 +124,153 @@ impl ProxyPage {
         Self {
             state: TableState::new().with_selected(0),
             scroll_state: ScrollbarState::default(),
+            filter: Filtered::default(),
+            sort: SortMode::default(),
+            page_len: 0,
+            rows: Rect::default(),
+            scrollbar: Rect::default(),
+            sweep: None,
         }
     }
+    /// The index of the selected proxy in the list last drawn.
     pub fn get_current_item(&self) -> Option<usize> {
-        self.state.selected()
+        self.state.selected().and_then(|i| self.filter.get(i))
+    }
+
+    fn select(&mut self, position: usize) {
+        self.state.select(Some(position));
+        self.scroll_state = self.scroll_state.position(position);
+    }
+
+    /// Selects the row of the proxy at `index`, if it is shown.
+    fn select_item(&mut self, index: usize) -> bool {
+        match self.filter.position(index) {
+            Some(position) => {
+                self.select(position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn sort(&mut self, proxies: &[SelectableProxy]) {
+        let sort = self.sort;
+        self.filter
+            .sort_by(|&a, &b| sort.compare(&proxies[a], &proxies[b]));
+    }
+
+    /// Recomputes the rows, keeping the selected proxy selected.
+    fn update(&mut self, proxies: &[SelectableProxy]) {
+        let selected = self.get_current_item();
+        self.filter
+            .update(proxies.len(), |i| proxy_fields(&proxies[i]));
+        self.sort(proxies);
+        if let Some(index) = selected {
+            self.select_item(index);
+        }
+    }
+
+    /// Clears the search and selects the proxy at `index`.
+    pub fn show(&mut self, index: usize, proxies: &[SelectableProxy]) {
+        self.filter.clear(proxies.len());
+        self.sort(proxies);
+        self.select_item(index);
+    }
+
+    /// Switches to the next [`SortMode`], keeping the selected proxy selected.
+    pub fn cycle_sort(&mut self, proxies: &[SelectableProxy]) {
+        self.sort = self.sort.next();
+        self.update(proxies);
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.filter.search.is_typing()
+    }
+
+    /// Handles a key typed into the search query.
+    pub fn type_key(&mut self, key: KeyEvent, proxies: &[SelectableProxy]) {
+        let selected = self.state.selected().unwrap_or(0);
+        let position = self
+            .filter
+            .type_key(key, selected, proxies.len(), |i| proxy_fields(&proxies[i]));
+        self.reselect(position, proxies);
+    }
+
+    /// Handles the search actions, returning whether `action` was one of them.
+    pub fn search_action(&mut self, action: Action, proxies: &[SelectableProxy]) -> bool {
+        let selected = self.state.selected().unwrap_or(0);
+        match self.filter.action(action, selected, proxies.len(), |i| {
+            proxy_fields(&proxies[i])
+        }) {
+            Some(position) => {
+                self.reselect(position, proxies);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Selects `position` of the search results, which are not sorted yet.
+    fn reselect(&mut self, position: usize, proxies: &[SelectableProxy]) {
+        let index = self.filter.get(position);
+        self.sort(proxies);
+        if !index.is_some_and(|i| self.select_item(i)) {
+            self.select(0);
+        }
+    }
+
+    /// Selects the proxy at `index`, clearing the search if it hides the proxy.
+    pub fn jump_to(&mut self, index: usize, proxies: &[SelectableProxy]) {
+        if !self.select_item(index) {
+            self.show(index, proxies);
+        }
+    }
+
+    /// Tests the latency of each of `proxies`, `concurrency` of them at a time.
+    pub fn start_sweep(
+        &mut self,
+        client: Arc<dyn ControllerClient>,
+        proxies: &[SelectableProxy],
+        concurrency: usize,
+    ) {
+        let names = proxies.iter().map(|p| p.name.clone()).collect();
+        self.sweep = Some(Sweep::start(client, names, concurrency));
     }
 
-    pub fn j(&mut self) {
-        self.state.select_next();
-        self.scroll_state = self.scroll_state.position(self.state.selected().unwrap());
+    pub fn is_sweeping(&self) -> bool {
+        self.sweep.is_some()
+    }
+
+    /// Takes the results of the sweep, returning its summary once it is over.
+    pub fn poll_sweep(&mut self) -> Option<String> {
+        let sweep = self.sweep.as_mut()?;
+        sweep.poll();
+        if !sweep.is_finished() {
+            return None;
+        }
+        self.sweep.take().map(|sweep| sweep.summary())
     }
 
-    pub fn k(&mut self) {
-        self.state.select_previous();
-        self.scroll_state = self.scroll_state.position(self.state.selected().unwrap());
+    /// Stops the sweep, returning how far it got.
+    pub fn cancel_sweep(&mut self) -> Option<String> {
+        let sweep = self.sweep.take()?;
+        sweep.cancel();
+        Some(format!("sweep cancelled at {}", sweep.progress()))
+    }
+
+    /// The latency shown for `proxy`: a pending one while a sweep tests it.
+    fn latency_cell(&self, proxy: &SelectableProxy, theme: &Theme) -> Cell<'static> {
+        let latency = match &self.sweep {
+            Some(sweep) if sweep.contains(&proxy.name) => match sweep.latency(&proxy.name) {
+                Some(latency) => Some(latency),
+                None => return Cell::from(Line::styled("…", theme.muted())),
+            },
+            _ => proxy.latency,
+        };
+        Cell::from(Line::styled(
+            latency.map_or("--".to_string(), |l| format!("{l}ms")),
+            theme.latency(latency),
+        ))
     }
 
     fn draw_table(
@@ -39,37 +279,87 @@ impl ProxyPage {
         buf: &mut ratatui::buffer::Buffer,
         proxies: &[SelectableProxy],
         selected: Option<usize>,
+        used_by: Option<&HashMap<String, Vec<Membership>>>,
+        theme: &Theme,
     ) {
-        let rows: Vec<Row> = proxies
+        let rows: Vec<Row> = self
+            .filter
+            .view()
             .iter()
-            .enumerate()
-            .map(|(i, p)| {
-                let row = Row::new(vec![
-                    p.name.clone(),
-                    p.proxy_type.str().to_owned(),
-                    p.latency.map_or("--".to_string(), |l| format!("{l}ms")),
-                    p.udp.to_string(),
-                ]);
+            .map(|&i| {
+                let p = &proxies[i];
+                let matched = self
+                    .filter
+                    .search
+                    .matches(&proxy_fields(p))
+                    .unwrap_or_default();
+                let udp = Line::from(p.udp.to_string());
+                let mut name = search::highlight(&p.name, &matched[0], theme);
+                let groups =
+                    used_by.map(|used_by| used_by.get(&p.name).map_or(&[][..], Vec::as_slice));
+                if let Some(groups) = groups {
+                    let marker = match groups.iter().any(|m| m.active) {
+                        true => Span::styled("● ", Style::new().fg(theme.highlight)),
+                        false => Span::raw("  "),
+                    };
+                    name.spans.insert(0, marker);
+                }
+                let mut cells = vec![
+                    Cell::from(name),
+                    Cell::from(search::highlight(p.proxy_type.str(), &matched[1], theme)),
+                    self.latency_cell(p, theme),
+                    Cell::from(if matched[2].is_empty() {
+                        udp
+                    } else {
+                        udp.style(theme.matched())
+                    }),
+                ];
+                if let Some(groups) = groups {
+                    cells.push(Cell::from(used_by_line(groups, theme)));
+                }
+                let row = Row::new(cells);
                 if selected.is_some_and(|s| s == i) {
-                    row.on_green().black()
+                    row.style(theme.badge(true))
                 } else {
                     row
                 }
             })
             .collect();
-        let widths = vec![
+        // the header and its margin take two lines
+        self.page_len = area.height.saturating_sub(2) as usize;
+        self.rows = Rect {
+            y: area.y + 2,
+            height: self.page_len as u16,
+            ..area
+        }
+        .intersection(area);
+        let mut widths = vec![
             ratatui::layout::Constraint::Fill(2),
             ratatui::layout::Constraint::Fill(1),
             ratatui::layout::Constraint::Fill(1),
             ratatui::layout::Constraint::Length(5),
         ];
+        let latency = match &self.sweep {
+            Some(sweep) => format!("Latency {}", sweep.progress()),
+            None => "Latency".to_string(),
+        };
+        let mut titles = vec!["Name", "Type", &latency, "UDP"];
+        if used_by.is_some() {
+            widths.push(ratatui::layout::Constraint::Fill(2));
+            titles.push("Used by");
+        }
 
         StatefulWidget::render(
             Table::new(rows, widths)
                 .header(
-                    Row::new(vec!["Name", "Type", "Latency", "UDP"])
-                        .bold()
-                        .bottom_margin(1),
+                    Row::new(titles.into_iter().enumerate().map(|(i, title)| {
+                        match self.sort.column() == Some(i) {
+                            true => format!("{title} ▲"),
+                            false => title.to_string(),
+                        }
+                    }))
+                    .bold()
+                    .bottom_margin(1),
                 )
                 .row_highlight_style(Style::new().reversed())
                 .highlight_symbol(">>"),
@@ -83,12 +373,12 @@ impl ProxyPage {
         &mut self,
         area: ratatui::layout::Rect,
         buf: &mut ratatui::buffer::Buffer,
-        proxies: &[SelectableProxy],
+        theme: &Theme,
     ) {
-        self.scroll_state = self.scroll_state.content_length(proxies.len());
+        self.scroll_state = self.scroll_state.content_length(self.filter.view().len());
         StatefulWidget::render(
             Scrollbar::default()
-                .thumb_style(Style::default().fg(ratatui::style::Color::Green))
+                .thumb_style(Style::default().fg(theme.accent))
                 .begin_symbol(Some("↑"))
                 .end_symbol(Some("↓")),
             area,
@@ -97,18 +387,32 @@ impl ProxyPage {
         );
     }
 
+    /// Draws the proxies matching the search, `selected` being the index of the active one.
+    /// With `used_by`, a column lists the groups of each proxy, and `●` marks the
+    /// proxies a group uses.
     pub fn draw(
         &mut self,
         area: ratatui::layout::Rect,
         buf: &mut ratatui::buffer::Buffer,
         proxies: &[SelectableProxy],
         selected: Option<usize>,
+        used_by: Option<&HashMap<String, Vec<Membership>>>,
+        theme: &Theme,
     ) {
-        if proxies.is_empty() {
+        self.update(proxies);
+        let area = self.filter.draw_prompt(area, buf, proxies.len());
+        self.rows = Rect::default();
+        self.scrollbar = Rect::default();
+
+        if self.filter.view().is_empty() {
             buf.set_string(
                 area.left(),
                 area.top(),
-                "No proxies available",
+                if proxies.is_empty() {
+                    "No proxies available"
+                } else {
+                    "No matching proxies"
+                },
                 Style::default().add_modifier(Modifier::BOLD),
             );
             return;
@@ -119,7 +423,87 @@ impl ProxyPage {
             ratatui::layout::Constraint::Length(1),
         ])
         .areas(area);
-        self.draw_table(table_area, buf, proxies, selected);
-        self.draw_scrollbar(scrollbar_area, buf, proxies);
+        self.draw_table(table_area, buf, proxies, selected, used_by, theme);
+        self.draw_scrollbar(scrollbar_area, buf, theme);
+        self.scrollbar = scrollbar_area;
+    }
+}
+
+impl Navigable for ProxyPage {
+    const GRID: bool = false;
+
+    fn len(&self) -> usize {
+        self.filter.view().len()
+    }
+    fn selected(&self) -> usize {
+        self.state.selected().unwrap_or(0)
+    }
+    fn select(&mut self, position: usize) {
+        ProxyPage::select(self, position);
+    }
+    fn row_len(&self) -> usize {
+        1
+    }
+    fn page_len(&self) -> usize {
+        self.page_len
+    }
+}
+
+impl Clickable for ProxyPage {
+    fn item_at(&self, position: Position) -> Option<usize> {
+        if !self.rows.contains(position) {
+            return None;
+        }
+        let row = self.state.offset() + (position.y - self.rows.y) as usize;
+        (row < self.filter.view().len()).then_some(row)
+    }
+    fn scrollbar(&self) -> Rect {
+        self.scrollbar
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::ProxyType;
+
+    fn proxy(name: &str, latency: Option<u64>, udp: bool) -> SelectableProxy {
+        SelectableProxy {
+            name: name.to_string(),
+            udp,
+            proxy_type: ProxyType::Vmess,
+            latency,
+        }
+    }
+
+    #[test]
+    fn test_sort_keeps_selection() {
+        let proxies = [
+            proxy("b", Some(0), true),
+            proxy("C", Some(300), false),
+            proxy("a", None, false),
+            proxy("d", Some(120), true),
+        ];
+        let mut page = ProxyPage::new();
+        page.update(&proxies);
+        page.select(1);
+
+        let order = |page: &ProxyPage| page.filter.view().to_vec();
+        page.cycle_sort(&proxies);
+        assert_eq!(page.sort, SortMode::Latency);
+        assert_eq!(order(&page), [3, 1, 0, 2]);
+        assert_eq!(page.get_current_item(), Some(1));
+
+        page.cycle_sort(&proxies);
+        assert_eq!(order(&page), [2, 0, 1, 3]);
+        assert_eq!(page.get_current_item(), Some(1));
+
+        page.cycle_sort(&proxies);
+        assert_eq!(order(&page), [0, 1, 2, 3]);
+        page.cycle_sort(&proxies);
+        assert_eq!(order(&page), [0, 3, 1, 2]);
+        page.cycle_sort(&proxies);
+        assert_eq!(page.sort, SortMode::Original);
+        assert_eq!(page.get_current_item(), Some(1));
     }
 }
@@ -0,0 +1,292 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Layout, Rect},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::{keymap::Action, theme::Theme};
+
+/// Finds the characters of `pattern` in `text`, ignoring case.
+///
+/// A substring match is preferred, otherwise the characters only have to come
+/// in order. Returns the char indices of the matched characters in `text`.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<Vec<usize>> {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    let text: Vec<char> = text.chars().collect();
+    let eq = |t: char, p: char| t.to_lowercase().eq(std::iter::once(p));
+
+    if pattern.len() <= text.len()
+        && let Some(start) = (0..=text.len() - pattern.len()).find(|&start| {
+            pattern
+                .iter()
+                .enumerate()
+                .all(|(i, &p)| eq(text[start + i], p))
+        })
+    {
+        return Some((start..start + pattern.len()).collect());
+    }
+
+    let mut positions = Vec::with_capacity(pattern.len());
+    let mut chars = text.iter().enumerate();
+    for &p in &pattern {
+        let (i, _) = chars.find(|&(_, &t)| eq(t, p))?;
+        positions.push(i);
+    }
+    Some(positions)
+}
+
+/// Styles the characters of `text` at `positions` as matches.
+pub fn highlight(text: &str, positions: &[usize], theme: &Theme) -> Line<'static> {
+    text.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if positions.contains(&i) {
+                Span::styled(c.to_string(), theme.matched())
+            } else {
+                Span::raw(c.to_string())
+            }
+        })
+        .collect()
+}
+
+/// The query typed after `/`.
+#[derive(Debug, Default)]
+pub struct Search {
+    query: String,
+    typing: bool,
+}
+
+impl Search {
+    pub fn is_typing(&self) -> bool {
+        self.typing
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.query.is_empty()
+    }
+
+    /// Starts typing a new query.
+    pub fn start(&mut self) {
+        self.typing = true;
+        self.query.clear();
+    }
+
+    /// Matches the query against the searchable fields of an item.
+    ///
+    /// Every whitespace separated term has to match one of the fields. Returns
+    /// the matched positions in each field.
+    pub fn matches(&self, fields: &[&str]) -> Option<Vec<Vec<usize>>> {
+        let mut positions = vec![vec![]; fields.len()];
+        for term in self.query.split_whitespace() {
+            let (field, matched) = fields
+                .iter()
+                .enumerate()
+                .find_map(|(i, field)| fuzzy_match(term, field).map(|m| (i, m)))?;
+            positions[field].extend(matched);
+        }
+        Some(positions)
+    }
+}
+
+/// A [`Search`] over a list, keeping the indices of the items it matches.
+///
+/// Pages select positions in [`Filtered::view`], which [`Filtered::get`] maps
+/// back to indices into the list.
+#[derive(Debug, Default)]
+pub struct Filtered {
+    pub search: Search,
+    view: Vec<usize>,
+}
+
+impl Filtered {
+    /// Recomputes the matching items of a list of `len` items, `fields` giving
+    /// the searchable fields of the item at an index.
+    pub fn update<'a>(&mut self, len: usize, fields: impl Fn(usize) -> Vec<&'a str>) {
+        self.view = (0..len)
+            .filter(|&i| self.search.matches(&fields(i)).is_some())
+            .collect();
+    }
+
+    /// Reorders the matching items, `compare` taking their indices.
+    pub fn sort_by(&mut self, compare: impl FnMut(&usize, &usize) -> std::cmp::Ordering) {
+        self.view.sort_by(compare);
+    }
+
+    pub fn view(&self) -> &[usize] {
+        &self.view
+    }
+
+    /// The index of the item at `position` in the view.
+    pub fn get(&self, position: usize) -> Option<usize> {
+        self.view.get(position).copied()
+    }
+
+    /// The position of the item at `index` in the view, if it matches.
+    pub fn position(&self, index: usize) -> Option<usize> {
+        self.view.iter().position(|&i| i == index)
+    }
+
+    /// Clears the query of a list of `len` items, which all match again.
+    pub fn clear(&mut self, len: usize) {
+        self.search = Search::default();
+        self.view = (0..len).collect();
+    }
+
+    /// Handles a key typed into the query, returning the position to select.
+    ///
+    /// `selected` is the current position in the view.
+    pub fn type_key<'a>(
+        &mut self,
+        key: KeyEvent,
+        selected: usize,
+        len: usize,
+        fields: impl Fn(usize) -> Vec<&'a str>,
+    ) -> usize {
+        let previous = self.get(selected);
+        match key.code {
+            KeyCode::Enter => self.search.typing = false,
+            KeyCode::Esc => {
+                self.search.typing = false;
+                self.search.query.clear();
+            }
+            KeyCode::Backspace => {
+                self.search.query.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.search.query.push(c)
+            }
+            _ => {}
+        }
+        self.reselect(previous, len, fields)
+    }
+
+    /// Handles [`Action::Search`], [`Action::NextMatch`], [`Action::PreviousMatch`]
+    /// and [`Action::Back`] clearing the query.
+    ///
+    /// Returns the position to select when the action applies.
+    pub fn action<'a>(
+        &mut self,
+        action: Action,
+        selected: usize,
+        len: usize,
+        fields: impl Fn(usize) -> Vec<&'a str>,
+    ) -> Option<usize> {
+        let previous = self.get(selected);
+        let matches = self.view.len();
+        match action {
+            Action::Search => self.search.start(),
+            Action::NextMatch if self.search.is_active() && matches > 0 => {
+                return Some((selected + 1) % matches);
+            }
+            Action::PreviousMatch if self.search.is_active() && matches > 0 => {
+                return Some((selected + matches - 1) % matches);
+            }
+            Action::Back if self.search.is_active() => self.search.query.clear(),
+            _ => return None,
+        }
+        Some(self.reselect(previous, len, fields))
+    }
+
+    /// Recomputes the view after the query changed, staying on the item at
+    /// `previous` while it matches.
+    fn reselect<'a>(
+        &mut self,
+        previous: Option<usize>,
+        len: usize,
+        fields: impl Fn(usize) -> Vec<&'a str>,
+    ) -> usize {
+        self.update(len, fields);
+        previous.and_then(|i| self.position(i)).unwrap_or(0)
+    }
+
+    /// Draws the query on the last line of `area` while there is one, returning
+    /// the rest of the area.
+    pub fn draw_prompt(&self, area: Rect, buf: &mut Buffer, total: usize) -> Rect {
+        if !self.search.typing && !self.search.is_active() {
+            return area;
+        }
+        let [rest, prompt] = Layout::vertical([
+            ratatui::layout::Constraint::Fill(1),
+            ratatui::layout::Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let cursor = if self.search.typing { "█" } else { "" };
+        let [query, count] = Layout::horizontal([
+            ratatui::layout::Constraint::Fill(1),
+            ratatui::layout::Constraint::Length(16),
+        ])
+        .areas(prompt);
+        Line::from(format!("/{}{cursor}", self.search.query)).render(query, buf);
+        Line::from(format!("{}/{total}", self.view.len()))
+            .right_aligned()
+            .render(count, buf);
+        rest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert_eq!(fuzzy_match("hk", "HK 01"), Some(vec![0, 1]));
+        assert_eq!(fuzzy_match("01", "HK 01"), Some(vec![3, 4]));
+        assert_eq!(fuzzy_match("h1", "HK 01"), Some(vec![0, 4]));
+        assert_eq!(fuzzy_match("", "HK 01"), Some(vec![]));
+        assert_eq!(fuzzy_match("kh", "HK 01"), None);
+        assert_eq!(fuzzy_match("hk 011", "HK 01"), None);
+        assert_eq!(fuzzy_match("日本", "🇯🇵 日本 01"), Some(vec![3, 4]));
+    }
+
+    #[test]
+    fn test_matches() {
+        let search = Search {
+            query: "jp udp".to_string(),
+            typing: false,
+        };
+        assert_eq!(
+            search.matches(&["JP 02", "Vmess", "udp"]),
+            Some(vec![vec![0, 1], vec![], vec![0, 1, 2]])
+        );
+        assert_eq!(search.matches(&["JP 01", "Vmess", ""]), None);
+    }
+
+    #[test]
+    fn test_filtered_maps_back() {
+        let names = ["HK 01", "JP 01", "HK 02", "US 01"];
+        let fields = |i: usize| vec![names[i]];
+        let key = |c| KeyEvent::from(KeyCode::Char(c));
+
+        let mut filtered = Filtered::default();
+        filtered.update(names.len(), fields);
+        // US 01 is selected
+        assert_eq!(filtered.action(Action::Search, 3, 4, fields), Some(3));
+        assert!(filtered.search.is_typing());
+        assert_eq!(filtered.type_key(key('h'), 3, 4, fields), 0);
+        assert_eq!(filtered.type_key(key('k'), 0, 4, fields), 0);
+        assert_eq!(filtered.view(), &[0, 2]);
+        assert_eq!(
+            filtered.type_key(KeyEvent::from(KeyCode::Enter), 0, 4, fields),
+            0
+        );
+        assert!(!filtered.search.is_typing());
+
+        assert_eq!(filtered.action(Action::NextMatch, 0, 4, fields), Some(1));
+        assert_eq!(filtered.get(1), Some(2));
+        assert_eq!(filtered.action(Action::NextMatch, 1, 4, fields), Some(0));
+        assert_eq!(
+            filtered.action(Action::PreviousMatch, 0, 4, fields),
+            Some(1)
+        );
+
+        // clearing the query keeps HK 02 selected
+        assert_eq!(filtered.action(Action::Back, 1, 4, fields), Some(2));
+        assert_eq!(filtered.view(), &[0, 1, 2, 3]);
+        assert_eq!(filtered.action(Action::Back, 2, 4, fields), None);
+        assert_eq!(filtered.action(Action::NextMatch, 2, 4, fields), None);
+    }
+}
@@ -0,0 +1,171 @@
+//! Latency tests of the proxies of a page one by one, shown as they come.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+    mpsc::Receiver,
+};
+
+use serde::Deserialize;
+
+use super::bulk::{self, Event};
+use crate::backend::ControllerClient;
+
+/// How the latency sweeps run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SweepConfig {
+    /// How many proxies are tested at the same time.
+    pub concurrency: usize,
+}
+
+impl Default for SweepConfig {
+    fn default() -> Self {
+        Self { concurrency: 8 }
+    }
+}
+
+impl SweepConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.concurrency == 0 {
+            return Err("the `concurrency` of `sweep` must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A latency test of each proxy of a list, running in the background.
+#[derive(Debug)]
+pub struct Sweep {
+    names: Vec<String>,
+    /// The delay of each of `names` once tested, 0 when it failed.
+    latencies: Vec<Option<u64>>,
+    cancel: Arc<AtomicBool>,
+    receiver: Receiver<Event<u64>>,
+}
+
+impl Sweep {
+    pub fn start(
+        client: Arc<dyn ControllerClient>,
+        names: Vec<String>,
+        concurrency: usize,
+    ) -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let receiver = bulk::spawn(
+            client,
+            names.clone(),
+            concurrency,
+            cancel.clone(),
+            // a delay of 0 is a failed test
+            |client, name| client.latency_test_proxy(name).unwrap_or(0),
+        );
+        Self {
+            latencies: vec![None; names.len()],
+            names,
+            cancel,
+            receiver,
+        }
+    }
+
+    /// Takes the results since the last call, returning whether there were some.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        for event in self.receiver.try_iter() {
+            if let Event::Finished(i, latency) = event {
+                self.latencies[i] = Some(latency);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Stops testing, the tests already sent still finishing in the background.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether `proxy` is part of the sweep.
+    pub fn contains(&self, proxy: &str) -> bool {
+        self.names.iter().any(|name| name == proxy)
+    }
+
+    /// The new delay of `proxy`, once it is tested.
+    pub fn latency(&self, proxy: &str) -> Option<u64> {
+        let i = self.names.iter().position(|name| name == proxy)?;
+        self.latencies[i]
+    }
+
+    fn tested(&self) -> usize {
+        self.latencies.iter().flatten().count()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.tested() == self.names.len()
+    }
+
+    /// How far the sweep got, such as `37/120`.
+    pub fn progress(&self) -> String {
+        format!("{}/{}", self.tested(), self.names.len())
+    }
+
+    /// What the sweep found once it is finished.
+    pub fn summary(&self) -> String {
+        let failed = self.latencies.iter().flatten().filter(|&&l| l == 0).count();
+        format!("tested {} proxies, {failed} failed", self.tested())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backend::{MemoryController, ProxyGroup, ProxyType, SelectableProxy},
+        tab::bulk::tests::wait,
+    };
+
+    fn finish(sweep: &mut Sweep) {
+        wait(|| {
+            sweep.poll();
+            sweep.is_finished()
+        });
+    }
+
+    #[test]
+    fn test_sweep() {
+        let latencies = [("HK 01", Some(48)), ("HK 02", Some(0)), ("JP 01", None)];
+        let group = ProxyGroup {
+            name: "Auto".to_string(),
+            now: None,
+            proxy_type: ProxyType::URLTest,
+            udp: true,
+            latency: None,
+            proxies: latencies
+                .iter()
+                .map(|&(name, latency)| SelectableProxy {
+                    name: name.to_string(),
+                    udp: true,
+                    proxy_type: ProxyType::Vmess,
+                    latency,
+                })
+                .collect(),
+        };
+        let client = Arc::new(MemoryController::new(vec![group], vec![]));
+        let names: Vec<String> = latencies.iter().map(|(name, _)| name.to_string()).collect();
+
+        let mut sweep = Sweep::start(client.clone(), names.clone(), 2);
+        assert_eq!(sweep.progress(), "0/3");
+        assert!(sweep.contains("JP 01") && !sweep.contains("US 01"));
+        finish(&mut sweep);
+        assert_eq!(sweep.latency("HK 01"), Some(48));
+        // failed and untested proxies time out
+        assert_eq!(sweep.latency("HK 02"), Some(0));
+        assert_eq!(sweep.latency("JP 01"), Some(0));
+        assert_eq!(sweep.summary(), "tested 3 proxies, 2 failed");
+
+        // a repeated name is tested each time
+        let repeated = vec![names[0].clone(), names[0].clone()];
+        let mut sweep = Sweep::start(client, repeated, 2);
+        finish(&mut sweep);
+        assert_eq!(sweep.progress(), "2/2");
+    }
+}
@@ -0,0 +1,375 @@
+//! The colors of the dashboard, built-in or from the config.
+
+use std::{collections::HashMap, str::FromStr};
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+/// The latencies under which a test is good or ok, slower ones being slow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LatencyThresholds {
+    pub good: u64,
+    pub ok: u64,
+}
+
+impl Default for LatencyThresholds {
+    fn default() -> Self {
+        Self { good: 200, ok: 500 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatencyQuality {
+    Good,
+    Ok,
+    Slow,
+    /// A delay of 0 is a failed test.
+    Failed,
+    Untested,
+}
+
+impl LatencyThresholds {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.good > self.ok {
+            return Err(format!(
+                "the `good` latency ({}) must not be above the `ok` one ({})",
+                self.good, self.ok
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn quality(&self, latency: Option<u64>) -> LatencyQuality {
+        match latency {
+            None => LatencyQuality::Untested,
+            Some(0) => LatencyQuality::Failed,
+            Some(l) if l <= self.good => LatencyQuality::Good,
+            Some(l) if l <= self.ok => LatencyQuality::Ok,
+            Some(_) => LatencyQuality::Slow,
+        }
+    }
+}
+
+/// A color of a theme that can be set in the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Slot {
+    Accent,
+    OnAccent,
+    Badge,
+    OnBadge,
+    Highlight,
+    Muted,
+    Error,
+    Gauge,
+    ScrollHint,
+    Upload,
+    Download,
+    LatencyGood,
+    LatencyOk,
+    LatencySlow,
+    LatencyFailed,
+}
+
+/// A color name such as `green` or `light-blue`, an index such as `208`, or `#rrggbb`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ConfigColor(Color);
+
+impl TryFrom<String> for ConfigColor {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Color::from_str(&s)
+            .map(Self)
+            .map_err(|_| format!("invalid color `{s}`"))
+    }
+}
+
+/// A theme of the config: a built-in one with some colors replaced.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ThemeConfig {
+    /// The built-in theme to start from, `dark` by default.
+    #[serde(default)]
+    pub base: Option<String>,
+    #[serde(flatten)]
+    pub colors: HashMap<Slot, ConfigColor>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Selected cards and rows, scrollbars and keys.
+    pub accent: Color,
+    /// Text on the accent color.
+    pub on_accent: Color,
+    /// The type badge of a card.
+    pub badge: Color,
+    pub on_badge: Color,
+    /// Search matches, the current tab and questions.
+    pub highlight: Color,
+    /// Secondary text.
+    pub muted: Color,
+    pub error: Color,
+    /// The empty part of a gauge.
+    pub gauge: Color,
+    /// The hint below cards when there are more.
+    pub scroll_hint: Color,
+    /// The traffic of a subscription.
+    pub upload: Color,
+    pub download: Color,
+    pub latency_good: Color,
+    pub latency_ok: Color,
+    pub latency_slow: Color,
+    pub latency_failed: Color,
+    /// Added to what is selected, which tells it apart without colors.
+    pub selection: Modifier,
+    pub thresholds: LatencyThresholds,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub const BUILT_IN: [&str; 3] = ["dark", "light", "high-contrast"];
+
+    pub fn dark() -> Self {
+        Self {
+            accent: Color::Green,
+            on_accent: Color::Black,
+            badge: Color::White,
+            on_badge: Color::Black,
+            highlight: Color::Yellow,
+            muted: Color::DarkGray,
+            error: Color::Red,
+            gauge: Color::DarkGray,
+            scroll_hint: Color::LightBlue,
+            upload: Color::Cyan,
+            download: Color::Blue,
+            latency_good: Color::Green,
+            latency_ok: Color::Yellow,
+            latency_slow: Color::LightRed,
+            latency_failed: Color::Red,
+            selection: Modifier::empty(),
+            thresholds: LatencyThresholds::default(),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            accent: Color::Blue,
+            on_accent: Color::White,
+            badge: Color::Black,
+            on_badge: Color::White,
+            highlight: Color::Magenta,
+            muted: Color::Gray,
+            error: Color::Red,
+            gauge: Color::Gray,
+            scroll_hint: Color::Cyan,
+            upload: Color::Cyan,
+            download: Color::Blue,
+            latency_good: Color::Green,
+            latency_ok: Color::Indexed(136),
+            latency_slow: Color::Indexed(166),
+            latency_failed: Color::Red,
+            selection: Modifier::empty(),
+            thresholds: LatencyThresholds::default(),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            accent: Color::LightYellow,
+            on_accent: Color::Black,
+            badge: Color::White,
+            on_badge: Color::Black,
+            highlight: Color::LightCyan,
+            muted: Color::Gray,
+            error: Color::LightRed,
+            gauge: Color::Gray,
+            scroll_hint: Color::White,
+            upload: Color::LightCyan,
+            download: Color::LightBlue,
+            latency_good: Color::LightGreen,
+            latency_ok: Color::LightYellow,
+            latency_slow: Color::LightMagenta,
+            latency_failed: Color::LightRed,
+            selection: Modifier::BOLD,
+            thresholds: LatencyThresholds::default(),
+        }
+    }
+
+    /// The terminal colors only, as asked by `NO_COLOR`.
+    pub fn no_color() -> Self {
+        Self {
+            accent: Color::Reset,
+            on_accent: Color::Reset,
+            badge: Color::Reset,
+            on_badge: Color::Reset,
+            highlight: Color::Reset,
+            muted: Color::Reset,
+            error: Color::Reset,
+            gauge: Color::Reset,
+            scroll_hint: Color::Reset,
+            upload: Color::Reset,
+            download: Color::Reset,
+            latency_good: Color::Reset,
+            latency_ok: Color::Reset,
+            latency_slow: Color::Reset,
+            latency_failed: Color::Reset,
+            selection: Modifier::BOLD | Modifier::REVERSED,
+            thresholds: LatencyThresholds::default(),
+        }
+    }
+
+    pub fn built_in(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// The theme called `name`, a built-in one or one of `themes`.
+    pub fn named(name: &str, themes: &HashMap<String, ThemeConfig>) -> Result<Self, String> {
+        if let Some(config) = themes.get(name) {
+            let base = config.base.as_deref().unwrap_or("dark");
+            let mut theme = Self::built_in(base)
+                .ok_or_else(|| format!("unknown base theme `{base}` of `{name}`"))?;
+            for (slot, color) in &config.colors {
+                theme.set(*slot, color.0);
+            }
+            return Ok(theme);
+        }
+        Self::built_in(name).ok_or_else(|| {
+            format!(
+                "unknown theme `{name}`, expected one of {} or a theme of `themes`",
+                Self::BUILT_IN.join(", ")
+            )
+        })
+    }
+
+    fn set(&mut self, slot: Slot, color: Color) {
+        let field = match slot {
+            Slot::Accent => &mut self.accent,
+            Slot::OnAccent => &mut self.on_accent,
+            Slot::Badge => &mut self.badge,
+            Slot::OnBadge => &mut self.on_badge,
+            Slot::Highlight => &mut self.highlight,
+            Slot::Muted => &mut self.muted,
+            Slot::Error => &mut self.error,
+            Slot::Gauge => &mut self.gauge,
+            Slot::ScrollHint => &mut self.scroll_hint,
+            Slot::Upload => &mut self.upload,
+            Slot::Download => &mut self.download,
+            Slot::LatencyGood => &mut self.latency_good,
+            Slot::LatencyOk => &mut self.latency_ok,
+            Slot::LatencySlow => &mut self.latency_slow,
+            Slot::LatencyFailed => &mut self.latency_failed,
+        };
+        *field = color;
+    }
+
+    /// Sets the latencies under which a test is good or ok.
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub fn with_thresholds(mut self, thresholds: LatencyThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Borders and titles of what is selected.
+    pub fn selected(&self) -> Style {
+        Style::new().fg(self.accent).add_modifier(self.selection)
+    }
+
+    /// The type badge of a card.
+    pub fn badge(&self, is_selected: bool) -> Style {
+        match is_selected {
+            true => Style::new()
+                .fg(self.on_accent)
+                .bg(self.accent)
+                .add_modifier(self.selection),
+            false => Style::new().fg(self.on_badge).bg(self.badge),
+        }
+    }
+
+    /// Search matches.
+    pub fn matched(&self) -> Style {
+        Style::new().fg(self.highlight).add_modifier(Modifier::BOLD)
+    }
+
+    pub fn muted(&self) -> Style {
+        Style::new().fg(self.muted)
+    }
+
+    /// A latency colored by its quality.
+    pub fn latency(&self, latency: Option<u64>) -> Style {
+        let color = match self.thresholds.quality(latency) {
+            LatencyQuality::Good => self.latency_good,
+            LatencyQuality::Ok => self.latency_ok,
+            LatencyQuality::Slow => self.latency_slow,
+            LatencyQuality::Failed => self.latency_failed,
+            LatencyQuality::Untested => self.muted,
+        };
+        Style::new().fg(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Stylize;
+
+    use super::*;
+
+    #[test]
+    fn test_latency_quality() {
+        let thresholds = LatencyThresholds { good: 100, ok: 300 };
+        assert_eq!(thresholds.quality(Some(40)), LatencyQuality::Good);
+        assert_eq!(thresholds.quality(Some(100)), LatencyQuality::Good);
+        assert_eq!(thresholds.quality(Some(101)), LatencyQuality::Ok);
+        assert_eq!(thresholds.quality(Some(2000)), LatencyQuality::Slow);
+        assert_eq!(thresholds.quality(Some(0)), LatencyQuality::Failed);
+        assert_eq!(thresholds.quality(None), LatencyQuality::Untested);
+        assert!(thresholds.validate().is_ok());
+        assert!(LatencyThresholds { good: 500, ok: 200 }.validate().is_err());
+
+        let theme = Theme::dark().with_thresholds(thresholds);
+        assert_eq!(theme.latency(Some(40)), Style::new().green());
+        assert_eq!(theme.latency(Some(2000)), Style::new().light_red());
+    }
+
+    #[test]
+    fn test_named_themes() {
+        let themes: HashMap<String, ThemeConfig> = serde_json::from_str(
+            r##"{
+                "mine": { "base": "light", "accent": "magenta", "latency_good": "#00ff00" },
+                "indexed": { "muted": "240" },
+                "broken": { "base": "solarized" }
+            }"##,
+        )
+        .unwrap();
+
+        let mine = Theme::named("mine", &themes).unwrap();
+        assert_eq!(mine.accent, Color::Magenta);
+        assert_eq!(mine.latency_good, Color::Rgb(0, 255, 0));
+        assert_eq!(mine.badge, Theme::light().badge);
+        assert_eq!(
+            Theme::named("indexed", &themes).unwrap().muted,
+            Color::Indexed(240)
+        );
+        assert_eq!(
+            Theme::named("high-contrast", &themes),
+            Ok(Theme::high_contrast())
+        );
+        assert!(Theme::named("broken", &themes).is_err());
+        assert!(Theme::named("solarized", &themes).is_err());
+
+        let invalid = serde_json::from_str::<ThemeConfig>(r#"{ "accent": "greenish" }"#);
+        assert!(invalid.unwrap_err().to_string().contains("greenish"));
+        assert!(serde_json::from_str::<ThemeConfig>(r#"{ "acent": "green" }"#).is_err());
+    }
+}
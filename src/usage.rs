@@ -0,0 +1,235 @@
+//! The traffic of the subscriptions over time, to forecast when their quota runs out.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::backend::{Provider, SubscriptionInfo};
+
+/// The traffic of a subscription at a time, in seconds since the epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sample {
+    pub time: i64,
+    pub used: u64,
+}
+
+/// The samples of each provider, oldest first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UsageHistory(HashMap<String, Vec<Sample>>);
+
+impl UsageHistory {
+    /// An unchanged traffic is sampled again after this many seconds.
+    const INTERVAL: i64 = 60 * 60;
+    /// Samples older than this many seconds are dropped.
+    const KEEP: i64 = 90 * 24 * 60 * 60;
+
+    /// Samples the traffic of the subscriptions, when it changed or the last
+    /// sample is old enough. Returns whether a sample was added.
+    pub fn record(&mut self, providers: &[Provider], now: i64) -> bool {
+        let mut recorded = false;
+        for provider in providers {
+            let Some(info) = &provider.subscription_info else {
+                continue;
+            };
+            let used = info.upload.unwrap_or(0) + info.download.unwrap_or(0);
+            let samples = self.0.entry(provider.name.clone()).or_default();
+            if samples
+                .last()
+                .is_some_and(|last| last.used == used && now - last.time < Self::INTERVAL)
+            {
+                continue;
+            }
+            samples.retain(|sample| now - sample.time < Self::KEEP);
+            samples.push(Sample { time: now, used });
+            recorded = true;
+        }
+        recorded
+    }
+
+    pub fn samples(&self, provider: &str) -> &[Sample] {
+        self.0.get(provider).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// The state of a subscription, and when its quota runs out at the current rate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Forecast {
+    pub upload: u64,
+    pub download: u64,
+    pub used: u64,
+    pub total: Option<u64>,
+    pub expire: Option<DateTime<Utc>>,
+    /// The average traffic a day since the quota was last reset.
+    pub daily: Option<f64>,
+    /// How many days the samples of `daily` span.
+    pub days: f64,
+    /// When the remaining quota is used at the rate of `daily`.
+    pub exhaustion: Option<DateTime<Utc>>,
+}
+
+impl Forecast {
+    /// The samples of `daily` have to span this many seconds.
+    const MIN_SPAN: i64 = 60 * 60;
+
+    pub fn new(info: &SubscriptionInfo, samples: &[Sample], now: DateTime<Utc>) -> Self {
+        let upload = info.upload.unwrap_or(0);
+        let download = info.download.unwrap_or(0);
+        let used = upload + download;
+
+        // a drop in the traffic is a new billing cycle
+        let start = samples
+            .windows(2)
+            .rposition(|pair| pair[1].used < pair[0].used)
+            .map_or(0, |i| i + 1);
+        let cycle = &samples[start..];
+        let (daily, days) = match (cycle.first(), cycle.last()) {
+            (Some(first), Some(last)) if last.time - first.time >= Self::MIN_SPAN => {
+                let days = (last.time - first.time) as f64 / 86400.0;
+                (
+                    Some(last.used.saturating_sub(first.used) as f64 / days),
+                    days,
+                )
+            }
+            _ => (None, 0.0),
+        };
+
+        let remaining = info.total.map(|total| total.saturating_sub(used));
+        let exhaustion = match (remaining, daily) {
+            (Some(0), _) => Some(now),
+            (Some(remaining), Some(daily)) if daily > 0.0 => {
+                let seconds = (remaining as f64 / daily * 86400.0).min(i64::MAX as f64 / 1e3);
+                now.checked_add_signed(TimeDelta::seconds(seconds as i64))
+            }
+            _ => None,
+        };
+
+        Self {
+            upload,
+            download,
+            used,
+            total: info.total,
+            expire: info
+                .expire
+                .filter(|&expire| expire > 0)
+                .and_then(|expire| Utc.timestamp_opt(expire, 0).single()),
+            daily,
+            days,
+            exhaustion,
+        }
+    }
+
+    /// The quota left, 0 once it is exceeded.
+    pub fn remaining(&self) -> Option<u64> {
+        self.total.map(|total| total.saturating_sub(self.used))
+    }
+
+    /// The whole days until the subscription expires, negative once it has.
+    pub fn days_to_expiry(&self, now: DateTime<Utc>) -> Option<i64> {
+        self.expire.map(|expire| (expire - now).num_days())
+    }
+
+    /// Whether the quota runs out before the subscription expires.
+    pub fn exhausted_before_expiry(&self) -> bool {
+        match (self.exhaustion, self.expire) {
+            (Some(exhaustion), Some(expire)) => exhaustion < expire,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB: u64 = 1 << 30;
+    const DAY: i64 = 24 * 60 * 60;
+
+    fn provider(used: u64) -> Provider {
+        Provider {
+            name: "A".to_string(),
+            vehicle_type: "HTTP".to_string(),
+            subscription_info: Some(SubscriptionInfo {
+                upload: Some(0),
+                download: Some(used),
+                total: Some(100 * GB),
+                expire: Some(30 * DAY),
+            }),
+            updated_at: String::new(),
+            proxies: vec![],
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let mut history = UsageHistory::default();
+        assert!(history.record(&[provider(GB)], 0));
+        // unchanged traffic is sampled hourly
+        assert!(!history.record(&[provider(GB)], 60));
+        assert!(history.record(&[provider(2 * GB)], 120));
+        assert!(history.record(&[provider(2 * GB)], 120 + 3600));
+        assert_eq!(history.samples("A").len(), 3);
+        assert!(history.samples("B").is_empty());
+
+        // old samples go
+        assert!(history.record(&[provider(3 * GB)], 91 * DAY));
+        assert_eq!(
+            history.samples("A"),
+            [Sample {
+                time: 91 * DAY,
+                used: 3 * GB
+            }]
+        );
+    }
+
+    #[test]
+    fn test_forecast() {
+        let samples = [
+            // the previous cycle
+            Sample {
+                time: 0,
+                used: 90 * GB,
+            },
+            Sample {
+                time: DAY,
+                used: 10 * GB,
+            },
+            Sample {
+                time: 3 * DAY,
+                used: 30 * GB,
+            },
+        ];
+        let info = provider(30 * GB).subscription_info.unwrap();
+        let now = Utc.timestamp_opt(3 * DAY, 0).unwrap();
+        let forecast = Forecast::new(&info, &samples, now);
+        assert_eq!(forecast.remaining(), Some(70 * GB));
+        assert_eq!(forecast.daily, Some(10.0 * GB as f64));
+        assert_eq!(forecast.days, 2.0);
+        assert_eq!(forecast.days_to_expiry(now), Some(27));
+        // 7 days left at 10 GB a day
+        assert_eq!(
+            forecast.exhaustion,
+            Some(Utc.timestamp_opt(10 * DAY, 0).unwrap())
+        );
+        assert!(forecast.exhausted_before_expiry());
+
+        // a slower rate lasts past the expiry
+        let forecast = Forecast::new(&info, &samples[1..2], now);
+        assert_eq!((forecast.daily, forecast.exhaustion), (None, None));
+        let slow = [
+            samples[1],
+            Sample {
+                time: 3 * DAY,
+                used: 12 * GB,
+            },
+        ];
+        assert!(!Forecast::new(&info, &slow, now).exhausted_before_expiry());
+
+        // over its quota, a subscription is exhausted already
+        let info = provider(120 * GB).subscription_info.unwrap();
+        let forecast = Forecast::new(&info, &[], now);
+        assert_eq!(forecast.remaining(), Some(0));
+        assert_eq!(forecast.exhaustion, Some(now));
+    }
+}
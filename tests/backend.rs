@@ -0,0 +1,222 @@
+use std::time::{Duration, Instant};
+
+use clashi::{
+    backend::{self, ControllerClient, Error, HttpController, MemoryController},
+    mock::{MockConfig, MockController},
+};
+
+fn start(config: MockConfig) -> (MockController, HttpController) {
+    let mock = MockController::start(config).unwrap();
+    let controller = HttpController::new(mock.url());
+    (mock, controller)
+}
+
+#[test]
+fn test_proxy_groups() {
+    let (_mock, controller) = start(MockConfig::default());
+    let groups = controller.proxy_groups().unwrap();
+
+    let names: Vec<&str> = groups.iter().map(|g| g.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "Auto",
+            "Balance",
+            "Fallback",
+            "GLOBAL",
+            "Proxy",
+            "Streaming"
+        ]
+    );
+
+    let proxy = groups.iter().find(|g| g.name == "Proxy").unwrap();
+    assert_eq!(proxy.now.as_deref(), Some("Auto"));
+    assert_eq!(proxy.proxies.len(), 7);
+    // the latency of a group follows its `now` down to a node
+    assert_eq!(proxy.latency, Some(48));
+    assert!(proxy.proxies[0].proxy_type.is_group());
+}
+
+#[test]
+fn test_proxy_providers() {
+    let (_mock, controller) = start(MockConfig::default());
+    let providers = controller.proxy_providers().unwrap();
+
+    let names: Vec<&str> = providers.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["Subscription A", "Subscription B"]);
+
+    let a = &providers[0];
+    assert_eq!(a.proxies.len(), 4);
+    let info = a.subscription_info.as_ref().unwrap();
+    assert_eq!(info.total, Some(200 * 1024 * 1024 * 1024));
+    assert!(providers[1].subscription_info.is_none());
+}
+
+#[test]
+fn test_select_proxy() {
+    let (_mock, controller) = start(MockConfig::default());
+    controller.select_proxy("Proxy", "JP 01").unwrap();
+
+    let groups = controller.proxy_groups().unwrap();
+    let proxy = groups.iter().find(|g| g.name == "Proxy").unwrap();
+    assert_eq!(proxy.now.as_deref(), Some("JP 01"));
+    assert_eq!(proxy.latency, Some(91));
+
+    assert!(matches!(
+        controller.select_proxy("Proxy", "JP 02"),
+        Err(Error::Api { status: 400, .. })
+    ));
+    assert!(matches!(
+        controller.select_proxy("Auto", "HK 02"),
+        Err(Error::Api { status: 400, .. })
+    ));
+    assert!(matches!(
+        controller.select_proxy("Nope", "HK 02"),
+        Err(Error::Api { status: 404, .. })
+    ));
+}
+
+#[test]
+fn test_latency_tests() {
+    let (_mock, controller) = start(MockConfig::default());
+
+    assert_eq!(controller.latency_test_proxy("HK 02").unwrap(), 63);
+    assert!(matches!(
+        controller.latency_test_proxy("SG 01"),
+        Err(Error::Api { status: 504, .. })
+    ));
+
+    let delays = controller.latency_test_group("Fallback").unwrap();
+    assert_eq!(delays.len(), 2);
+    assert_eq!(delays["JP 01"], 91);
+    assert!(!delays.contains_key("SG 01"));
+
+    // a failed test is recorded in the history as 0
+    let groups = controller.proxy_groups().unwrap();
+    let fallback = groups.iter().find(|g| g.name == "Fallback").unwrap();
+    assert_eq!(fallback.proxies[0].latency, Some(0));
+}
+
+#[test]
+fn test_provider_actions() {
+    let (_mock, controller) = start(MockConfig::default());
+    let before = controller.proxy_providers().unwrap()[0].updated_at.clone();
+
+    controller.update_proxy_provider("Subscription A").unwrap();
+    controller.latency_test_provider("Subscription B").unwrap();
+
+    let providers = controller.proxy_providers().unwrap();
+    assert_ne!(providers[0].updated_at, before);
+    // an update swaps the first proxy for one the provider did not have
+    let names: Vec<&str> = providers[0]
+        .proxies
+        .iter()
+        .map(|p| p.name.as_str())
+        .collect();
+    assert_eq!(names, ["HK 02", "JP 01", "JP 02", "US 01"]);
+    let sg = providers[1]
+        .proxies
+        .iter()
+        .find(|p| p.name == "SG 01")
+        .unwrap();
+    assert_eq!(sg.latency, Some(0));
+
+    assert!(matches!(
+        controller.update_proxy_provider("default2"),
+        Err(Error::Api { status: 404, .. })
+    ));
+}
+
+#[test]
+fn test_traffic_and_connections() {
+    let (_mock, controller) =
+        start(MockConfig::default().traffic_interval(Duration::from_millis(10)));
+
+    let mut samples = vec![];
+    controller
+        .traffic(&mut |sample| {
+            samples.push((sample.up, sample.down));
+            samples.len() < 2
+        })
+        .unwrap();
+    assert_eq!(samples, [(2048, 16384), (3072, 24576)]);
+
+    let connections = controller.get_raw(&["connections"]).unwrap();
+    assert_eq!(connections["connections"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_failure_injection() {
+    let (_mock, controller) = start(MockConfig::default().failure_rate(1.0));
+    assert!(matches!(
+        controller.proxy_groups(),
+        Err(Error::Api { status: 500, .. })
+    ));
+
+    // the same seed fails the same requests
+    let outcomes = || {
+        let (_mock, controller) = start(MockConfig::default().failure_rate(0.5).seed(7));
+        (0..8)
+            .map(|_| controller.proxy_groups().is_ok())
+            .collect::<Vec<_>>()
+    };
+    let first = outcomes();
+    assert_eq!(first, outcomes());
+    assert!(first.contains(&true) && first.contains(&false));
+}
+
+#[test]
+fn test_latency_injection() {
+    let (_mock, controller) = start(MockConfig::default().latency(Duration::from_millis(150)));
+    let start = Instant::now();
+    controller.proxy_providers().unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(150));
+}
+
+#[test]
+fn test_memory_controller() {
+    let (_mock, controller) = start(MockConfig::default());
+    let memory = MemoryController::new(
+        controller.proxy_groups().unwrap(),
+        controller.proxy_providers().unwrap(),
+    );
+
+    memory.select_proxy("Streaming", "JP 02").unwrap();
+    let groups = memory.proxy_groups().unwrap();
+    let streaming = groups.iter().find(|g| g.name == "Streaming").unwrap();
+    assert_eq!(streaming.now.as_deref(), Some("JP 02"));
+    assert_eq!(streaming.latency, Some(840));
+    assert!(matches!(
+        memory.select_proxy("Auto", "HK 02"),
+        Err(Error::Api { status: 400, .. })
+    ));
+
+    assert_eq!(memory.latency_test_proxy("US 01").unwrap(), 182);
+    assert!(memory.latency_test_proxy("SG 01").is_err());
+    assert_eq!(memory.latency_test_group("Balance").unwrap().len(), 2);
+
+    let read_only = MemoryController::new(groups, vec![]).read_only();
+    assert!(matches!(
+        read_only.select_proxy("Streaming", "US 01"),
+        Err(Error::ReadOnly)
+    ));
+}
+
+#[test]
+fn test_used_by() {
+    let (_mock, controller) = start(MockConfig::default());
+    let used_by = backend::build_used_by(&controller.proxy_groups().unwrap());
+
+    let groups = |proxy: &str| -> Vec<(&str, bool)> {
+        used_by[proxy]
+            .iter()
+            .map(|m| (m.group.as_str(), m.active))
+            .collect()
+    };
+    assert_eq!(
+        groups("HK 01"),
+        [("Auto", true), ("Balance", true), ("Proxy", false)]
+    );
+    assert_eq!(groups("Auto"), [("GLOBAL", false), ("Proxy", true)]);
+    assert_eq!(groups("REJECT"), [("GLOBAL", false)]);
+}
@@ -0,0 +1,758 @@
+use std::sync::Arc;
+
+use clashi::{
+    alert::{AlertKind, AlertThresholds},
+    backend::{ControllerClient, HttpController, MemoryController, Store},
+    keymap::{Keymap, KeysConfig},
+    mock::{MockConfig, MockController},
+    state::State,
+    tab::{BoardWidget, CardSize, CardSizes, GroupView},
+    theme::{LatencyThresholds, Theme},
+    usage::UsageHistory,
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier},
+};
+
+fn memory_controller() -> Arc<MemoryController> {
+    let mock = MockController::start(MockConfig::default()).unwrap();
+    let controller = HttpController::new(mock.url());
+    Arc::new(MemoryController::new(
+        controller.proxy_groups().unwrap(),
+        controller.proxy_providers().unwrap(),
+    ))
+}
+
+/// A board on the mock itself, whose provider updates change the proxies.
+fn mock_board() -> (MockController, BoardWidget) {
+    let mock = MockController::start(MockConfig::default()).unwrap();
+    let client = Arc::new(HttpController::new(mock.url()));
+    (mock, BoardWidget::new(Store::new(client).unwrap()))
+}
+
+fn draw(board: &mut BoardWidget) -> Buffer {
+    let area = Rect::new(0, 0, 100, 30);
+    let mut buf = Buffer::empty(area);
+    board.draw_tab(area, &mut buf);
+    buf
+}
+
+/// The drawn symbols, row after row.
+fn screen(board: &mut BoardWidget) -> String {
+    let buf = draw(board);
+    buf.content().iter().map(|cell| cell.symbol()).collect()
+}
+
+fn press(board: &mut BoardWidget, code: KeyCode) {
+    board.key_event(KeyEvent::from(code));
+    draw(board);
+}
+
+/// Ticks until the background work is over.
+fn wait(board: &mut BoardWidget) {
+    let start = std::time::Instant::now();
+    while board.is_busy() {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+        board.tick();
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_select_from_proxy_page() {
+    let client = memory_controller();
+    let mut board = BoardWidget::new(Store::new(client.clone()).unwrap());
+    draw(&mut board);
+
+    // groups are sorted: Auto, Balance, Fallback, GLOBAL, Proxy, Streaming
+    for _ in 0..5 {
+        press(&mut board, KeyCode::Right);
+    }
+    press(&mut board, KeyCode::Enter);
+    // Streaming lists Proxy, JP 01, JP 02, ...
+    press(&mut board, KeyCode::Char('j'));
+    press(&mut board, KeyCode::Char('j'));
+    press(&mut board, KeyCode::Enter);
+
+    assert_eq!(board.message(), None);
+    let groups = client.proxy_groups().unwrap();
+    let streaming = groups.iter().find(|g| g.name == "Streaming").unwrap();
+    assert_eq!(streaming.now.as_deref(), Some("JP 02"));
+}
+
+#[test]
+fn test_read_only_actions_report_errors() {
+    let client = memory_controller();
+    let groups = client.proxy_groups().unwrap();
+    let client = Arc::new(MemoryController::new(groups, vec![]).read_only());
+    let mut board = BoardWidget::new(Store::new(client).unwrap());
+    draw(&mut board);
+
+    assert!(board.is_read_only());
+    press(&mut board, KeyCode::Char('r'));
+    assert_eq!(board.message(), Some("not available in read-only mode"));
+}
+
+#[test]
+fn test_search_selects_the_filtered_proxy() {
+    let client = memory_controller();
+    let mut board = BoardWidget::new(Store::new(client.clone()).unwrap());
+    draw(&mut board);
+
+    press(&mut board, KeyCode::Char('/'));
+    assert!(board.is_searching());
+    for c in "stream".chars() {
+        press(&mut board, KeyCode::Char(c));
+    }
+    press(&mut board, KeyCode::Enter);
+    press(&mut board, KeyCode::Enter);
+
+    // Streaming lists Proxy, JP 01, JP 02, US 01, US 02: only the last two match
+    press(&mut board, KeyCode::Char('/'));
+    for c in "us".chars() {
+        press(&mut board, KeyCode::Char(c));
+    }
+    press(&mut board, KeyCode::Enter);
+    press(&mut board, KeyCode::Char('n'));
+    press(&mut board, KeyCode::Enter);
+
+    assert_eq!(board.message(), None);
+    let groups = client.proxy_groups().unwrap();
+    let streaming = groups.iter().find(|g| g.name == "Streaming").unwrap();
+    assert_eq!(streaming.now.as_deref(), Some("US 02"));
+}
+
+#[test]
+fn test_go_to_proxy_and_select_it() {
+    let client = memory_controller();
+    let mut board = BoardWidget::new(Store::new(client.clone()).unwrap());
+    draw(&mut board);
+
+    board.key_event(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL));
+    draw(&mut board);
+    assert_eq!(
+        board.key_hints().to_string(),
+        "↓/ctrl+n/↑/ctrl+p select  enter go to  esc close"
+    );
+    // the motion keys of the pages type in the query
+    for c in "jp 02 stream".chars() {
+        press(&mut board, KeyCode::Char(c));
+    }
+    press(&mut board, KeyCode::Enter);
+    // Streaming is a selector, so selecting JP 02 there is offered
+    assert!(board.is_searching());
+    press(&mut board, KeyCode::Char('y'));
+    assert!(!board.is_searching());
+
+    assert_eq!(board.message(), None);
+    let groups = client.proxy_groups().unwrap();
+    let streaming = groups.iter().find(|g| g.name == "Streaming").unwrap();
+    assert_eq!(streaming.now.as_deref(), Some("JP 02"));
+
+    // the proxy page of Streaming is open on JP 02
+    press(&mut board, KeyCode::Char('j'));
+    press(&mut board, KeyCode::Enter);
+    let groups = client.proxy_groups().unwrap();
+    let streaming = groups.iter().find(|g| g.name == "Streaming").unwrap();
+    assert_eq!(streaming.now.as_deref(), Some("US 01"));
+}
+
+#[test]
+fn test_sort_keeps_the_selected_proxy() {
+    let client = memory_controller();
+    let mut board = BoardWidget::new(Store::new(client.clone()).unwrap());
+    draw(&mut board);
+
+    for _ in 0..5 {
+        press(&mut board, KeyCode::Right);
+    }
+    press(&mut board, KeyCode::Enter);
+    // JP 02 in Proxy, JP 01, JP 02, US 01, US 02
+    press(&mut board, KeyCode::Char('j'));
+    press(&mut board, KeyCode::Char('j'));
+    // by latency: Proxy, JP 01, US 01, JP 02, US 02
+    press(&mut board, KeyCode::Char('s'));
+    press(&mut board, KeyCode::Char('j'));
+    press(&mut board, KeyCode::Enter);
+
+    assert_eq!(board.message(), None);
+    let groups = client.proxy_groups().unwrap();
+    let streaming = groups.iter().find(|g| g.name == "Streaming").unwrap();
+    assert_eq!(streaming.now.as_deref(), Some("US 02"));
+}
+
+#[test]
+fn test_navigation_keys() {
+    let client = memory_controller();
+    let mut board = BoardWidget::new(Store::new(client.clone()).unwrap());
+    draw(&mut board);
+
+    // the sixth group is Streaming
+    press(&mut board, KeyCode::Char('6'));
+    press(&mut board, KeyCode::Char('G'));
+    press(&mut board, KeyCode::Enter);
+    for code in [
+        KeyCode::PageDown,
+        KeyCode::PageUp,
+        KeyCode::Home,
+        KeyCode::End,
+    ] {
+        press(&mut board, code);
+    }
+    board.key_event(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+    board.key_event(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL));
+    draw(&mut board);
+
+    // from US 02 back to the active US 01, then up to JP 02
+    press(&mut board, KeyCode::Char('a'));
+    press(&mut board, KeyCode::Up);
+    press(&mut board, KeyCode::Enter);
+
+    assert_eq!(board.message(), None);
+    let groups = client.proxy_groups().unwrap();
+    let streaming = groups.iter().find(|g| g.name == "Streaming").unwrap();
+    assert_eq!(streaming.now.as_deref(), Some("JP 02"));
+}
+
+#[test]
+fn test_custom_keymap() {
+    let client = memory_controller();
+    let config: KeysConfig =
+        serde_json::from_str(r#"{ "groups": { "open": ["o"], "test_group": ["enter"] } }"#)
+            .unwrap();
+    let keymap = Keymap::from_config(&config).unwrap();
+    let mut board = BoardWidget::new(Store::new(client.clone()).unwrap()).with_keymap(keymap);
+    draw(&mut board);
+    assert_eq!(board.message(), None);
+
+    // Streaming, then JP 02 in Proxy, JP 01, JP 02, ...
+    press(&mut board, KeyCode::Char('6'));
+    press(&mut board, KeyCode::Char('G'));
+    press(&mut board, KeyCode::Char('o'));
+    press(&mut board, KeyCode::Char('3'));
+    press(&mut board, KeyCode::Char('g'));
+    press(&mut board, KeyCode::Char('g'));
+    press(&mut board, KeyCode::Enter);
+    assert_eq!(board.message(), None);
+    let groups = client.proxy_groups().unwrap();
+    let streaming = groups.iter().find(|g| g.name == "Streaming").unwrap();
+    assert_eq!(streaming.now.as_deref(), Some("JP 02"));
+
+    press(&mut board, KeyCode::Char('q'));
+    assert!(board.should_quit());
+
+    let config: KeysConfig =
+        serde_json::from_str(r#"{ "groups": { "test_group": ["enter"] } }"#).unwrap();
+    let board = BoardWidget::new(Store::new(client).unwrap())
+        .with_keymap(Keymap::from_config(&config).unwrap());
+    assert_eq!(
+        board.message(),
+        Some("key conflict: `enter` is bound to both `open` and `test_group` in `groups`")
+    );
+}
+
+#[test]
+fn test_key_hints_and_help() {
+    let mut board = BoardWidget::new(Store::new(memory_controller()).unwrap());
+    let hints = |board: &BoardWidget| board.key_hints().to_string();
+    draw(&mut board);
+    assert!(
+        hints(&board)
+            .starts_with("enter/space open  r test group  v switch view  | split  / search")
+    );
+
+    press(&mut board, KeyCode::Enter);
+    assert!(hints(&board).starts_with("enter/space select  r test proxy  R test group  s sort"));
+
+    // the help lists the page's own actions, then the global ones
+    press(&mut board, KeyCode::Char('?'));
+    let help = screen(&mut board);
+    let select = help.find("enter/space  select").unwrap();
+    let quit = help.find("q  quit").unwrap();
+    assert!(select < quit);
+    assert_eq!(hints(&board), "j/↓/k/↑ scroll  other keys close");
+
+    // motions scroll it, other keys close it without doing anything
+    press(&mut board, KeyCode::Char('j'));
+    assert!(screen(&mut board).contains(" Keys "));
+    press(&mut board, KeyCode::Char('q'));
+    assert!(!board.should_quit());
+    assert!(!screen(&mut board).contains(" Keys "));
+
+    press(&mut board, KeyCode::Char('/'));
+    assert_eq!(hints(&board), "enter keep the filter  esc clear the query");
+}
+
+#[test]
+fn test_mouse() {
+    let client = memory_controller();
+    let mut board = BoardWidget::new(Store::new(client.clone()).unwrap());
+    let click = |board: &mut BoardWidget, column, row| {
+        for kind in [
+            MouseEventKind::Down(MouseButton::Left),
+            MouseEventKind::Up(MouseButton::Left),
+        ] {
+            board.mouse_event(MouseEvent {
+                kind,
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            });
+        }
+        draw(board);
+    };
+    let mut buf = Buffer::empty(Rect::new(0, 0, 100, 1));
+    board.draw_tab_pane(Rect::new(0, 0, 100, 1), &mut buf);
+    // " Group | Provider "
+    click(&mut board, 10, 0);
+    assert!(screen(&mut board).contains("last update"));
+    click(&mut board, 3, 0);
+    draw(&mut board);
+
+    // Streaming is the third card of the second row, and a double click opens it
+    click(&mut board, 70, 5);
+    assert!(!screen(&mut board).contains("Latency"));
+    click(&mut board, 70, 5);
+    assert!(screen(&mut board).contains("Latency"));
+
+    // the rows start below the header, and the wheel does not move past the last one
+    let groups = client.proxy_groups().unwrap();
+    let streaming = groups.iter().find(|g| g.name == "Streaming").unwrap();
+    let wheel = MouseEvent {
+        kind: MouseEventKind::ScrollDown,
+        column: 10,
+        row: 10,
+        modifiers: KeyModifiers::NONE,
+    };
+    for _ in 0..10 {
+        board.mouse_event(wheel);
+    }
+    click(&mut board, 10, 3);
+    click(&mut board, 10, 3);
+    assert_eq!(board.message(), None);
+    let groups = client.proxy_groups().unwrap();
+    let now = groups
+        .iter()
+        .find(|g| g.name == "Streaming")
+        .unwrap()
+        .now
+        .clone();
+    assert_eq!(now, Some(streaming.proxies[1].name.clone()));
+}
+
+#[test]
+fn test_themes() {
+    let client = memory_controller();
+    let theme = Theme::dark().with_thresholds(LatencyThresholds { good: 50, ok: 100 });
+    let mut board = BoardWidget::new(Store::new(client.clone()).unwrap()).with_theme(theme);
+    // the latencies of Streaming: 48ms for Proxy, 91ms for JP 01, 840ms for JP 02
+    press(&mut board, KeyCode::Char('6'));
+    press(&mut board, KeyCode::Char('G'));
+    press(&mut board, KeyCode::Enter);
+    let buf = draw(&mut board);
+    let latency_color = |name: &str| {
+        let y = (0..buf.area.height)
+            .find(|&y| {
+                let row: String = (0..buf.area.width).map(|x| buf[(x, y)].symbol()).collect();
+                row.contains(name)
+            })
+            .unwrap_or_else(|| panic!("{name} is not drawn"));
+        // the last digit of the latency, e.g. in `91ms` next to `Vmess`
+        let x = (1..buf.area.width - 1)
+            .find(|&x| buf[(x, y)].symbol() == "m" && buf[(x + 1, y)].symbol() == "s")
+            .unwrap()
+            - 1;
+        buf[(x, y)].fg
+    };
+    assert_eq!(latency_color("Proxy"), Color::Green);
+    assert_eq!(latency_color("JP 01"), Color::Yellow);
+    assert_eq!(latency_color("JP 02"), Color::LightRed);
+
+    let mut board = BoardWidget::new(Store::new(client).unwrap()).with_theme(Theme::no_color());
+    let buf = draw(&mut board);
+    assert!(
+        buf.content()
+            .iter()
+            .all(|cell| cell.fg == Color::Reset && cell.bg == Color::Reset)
+    );
+    // the selection is still told apart
+    assert!(buf[(0, 0)].modifier.contains(Modifier::REVERSED));
+}
+
+#[test]
+fn test_narrow_and_short_terminals() {
+    let cards = CardSizes {
+        groups: CardSize {
+            height: 5,
+            width: 30,
+        },
+        ..CardSizes::default()
+    };
+    let mut board =
+        BoardWidget::new(Store::new(memory_controller()).unwrap()).with_card_sizes(cards);
+    let screen = |board: &mut BoardWidget, width, height| {
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        board.draw_tab(area, &mut buf);
+        buf.content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>()
+    };
+    // narrower than a card, the groups are listed in one column
+    assert!(screen(&mut board, 20, 10).contains("Auto"));
+    press(&mut board, KeyCode::Char('G'));
+    let narrow = screen(&mut board, 20, 10);
+    assert!(narrow.contains("Streaming") && !narrow.contains("Balance"));
+
+    // the selection stays in sight when the terminal grows or shrinks
+    assert!(screen(&mut board, 100, 30).contains("Balance"));
+    assert!(screen(&mut board, 100, 3).contains("Streaming"));
+    screen(&mut board, 0, 0);
+}
+
+#[test]
+fn test_group_views() {
+    let client = memory_controller();
+    let path = std::env::temp_dir()
+        .join(format!("clashi-views-{}", std::process::id()))
+        .join("state.json");
+    let mut board = BoardWidget::new(Store::new(client.clone()).unwrap())
+        .with_state(State::default(), Some(path.clone()));
+    draw(&mut board);
+    press(&mut board, KeyCode::Char('l'));
+
+    // the table keeps the selected group
+    press(&mut board, KeyCode::Char('v'));
+    let table = screen(&mut board);
+    assert!(table.contains("Proxies") && table.contains(">>Balance"));
+    assert_eq!(State::load(&path).unwrap().group_view, GroupView::Table);
+
+    // searching and moving through the tree
+    press(&mut board, KeyCode::Char('v'));
+    assert!(screen(&mut board).contains("└─ Balance"));
+    press(&mut board, KeyCode::Char('/'));
+    for c in "fall".chars() {
+        press(&mut board, KeyCode::Char(c));
+    }
+    press(&mut board, KeyCode::Enter);
+    let tree = screen(&mut board);
+    // the groups leading to a match stay
+    assert!(tree.contains(">>│  └─ Fallback") && tree.contains("├─ Proxy"));
+    assert!(!tree.contains("Balance"));
+    press(&mut board, KeyCode::Enter);
+    assert!(screen(&mut board).contains("JP 01"));
+
+    // the view is restored on the next run
+    let state = State::load(&path).unwrap();
+    assert_eq!(state.group_view, GroupView::Tree);
+    let mut board = BoardWidget::new(Store::new(client).unwrap()).with_state(state, None);
+    assert!(screen(&mut board).contains("├─ Proxy"));
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_split_layout() {
+    let mut board = BoardWidget::new(Store::new(memory_controller()).unwrap());
+    let hints = |board: &BoardWidget| board.key_hints().to_string();
+    draw(&mut board);
+    press(&mut board, KeyCode::Char('|'));
+    assert!(board.state().split);
+    let split = screen(&mut board);
+    assert!(split.contains("┌ Auto ") && split.contains("Latency") && split.contains(">>HK 01"));
+
+    // the proxies follow the selected group
+    press(&mut board, KeyCode::Char('j'));
+    assert!(screen(&mut board).contains("┌ Balance "));
+    press(&mut board, KeyCode::Enter);
+    assert!(hints(&board).starts_with("enter/space select"));
+    press(&mut board, KeyCode::Char('j'));
+    assert!(screen(&mut board).contains(">>HK 02"));
+    press(&mut board, KeyCode::Esc);
+    assert!(hints(&board).starts_with("enter/space open"));
+
+    // a click focuses the pane under the mouse
+    board.mouse_event(MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column: 60,
+        row: 3,
+        modifiers: KeyModifiers::NONE,
+    });
+    assert!(hints(&board).starts_with("enter/space select"));
+    assert!(screen(&mut board).contains(">>HK 01"));
+
+    // a narrow terminal shows the focused page alone
+    let area = Rect::new(0, 0, 60, 30);
+    let mut buf = Buffer::empty(area);
+    board.draw_tab(area, &mut buf);
+    let narrow: String = buf.content().iter().map(|cell| cell.symbol()).collect();
+    assert!(narrow.contains("Latency") && !narrow.contains("LoadBalance"));
+    press(&mut board, KeyCode::Char('|'));
+    assert!(!screen(&mut board).contains("┌ Balance "));
+}
+
+#[test]
+fn test_provider_detail() {
+    let client = memory_controller();
+    // Subscription A used 20G less two days ago, and has 140G left for 20 days
+    let mut providers = client.proxy_providers().unwrap();
+    let info = providers[0].subscription_info.as_mut().unwrap();
+    *info.download.as_mut().unwrap() -= 20 << 30;
+    let mut usage = UsageHistory::default();
+    usage.record(
+        &providers,
+        chrono::Utc::now().timestamp() - 2 * 24 * 60 * 60,
+    );
+    let state = State {
+        usage,
+        ..State::default()
+    };
+    let mut board = BoardWidget::new(Store::new(client).unwrap()).with_state(state, None);
+    assert_eq!(board.state().usage.samples("Subscription A").len(), 2);
+
+    press(&mut board, KeyCode::Tab);
+    press(&mut board, KeyCode::Char('i'));
+    let detail = screen(&mut board);
+    assert!(detail.contains("Remaining   140.0G"));
+    assert!(detail.contains("Average     10.0G a day over 2.0 days"));
+    assert!(detail.contains("at this rate, before it expires"));
+    assert!(
+        board
+            .key_hints()
+            .to_string()
+            .starts_with("enter/space open  f update  esc back")
+    );
+
+    press(&mut board, KeyCode::Enter);
+    assert!(screen(&mut board).contains("Latency"));
+    press(&mut board, KeyCode::Esc);
+    press(&mut board, KeyCode::Char('l'));
+    press(&mut board, KeyCode::Char('i'));
+    assert!(screen(&mut board).contains("No subscription information"));
+    press(&mut board, KeyCode::Esc);
+    assert!(screen(&mut board).contains("last update"));
+}
+
+#[test]
+fn test_alerts() {
+    let client = memory_controller();
+    let board = BoardWidget::new(Store::new(client).unwrap());
+    assert!(board.alerts().is_empty());
+
+    // Subscription A expires in 20 days
+    let mut board = board.with_alerts(AlertThresholds {
+        expiry_days: 30,
+        ..AlertThresholds::default()
+    });
+    let alerts = board.alerts();
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].provider, "Subscription A");
+    assert!(matches!(alerts[0].kind, AlertKind::Expiring(19..=20)));
+
+    let area = Rect::new(0, 0, 30, 1);
+    let mut buf = Buffer::empty(area);
+    board.draw_tab_pane(area, &mut buf);
+    let tabs: String = buf.content().iter().map(|cell| cell.symbol()).collect();
+    assert_eq!(tabs.trim_end(), " Group | Provider  1");
+    assert!(buf[(18, 0)].modifier.contains(Modifier::REVERSED));
+
+    // the badge is part of the title
+    board.mouse_event(MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column: 18,
+        row: 0,
+        modifiers: KeyModifiers::NONE,
+    });
+    assert!(screen(&mut board).contains("last update"));
+}
+
+#[test]
+fn test_bulk_provider_actions() {
+    let (_mock, mut board) = mock_board();
+
+    press(&mut board, KeyCode::Tab);
+    press(&mut board, KeyCode::Char('F'));
+    wait(&mut board);
+    assert_eq!(board.message(), Some("updated 2 providers"));
+    assert_eq!(screen(&mut board).matches("✓ updated").count(), 2);
+    // the changes are kept like those of a single update
+    press(&mut board, KeyCode::Char('i'));
+    assert!(screen(&mut board).contains("+1 added  -1 removed  3 kept"));
+    press(&mut board, KeyCode::Esc);
+
+    // only the marked ones
+    press(&mut board, KeyCode::Char('l'));
+    press(&mut board, KeyCode::Char('m'));
+    assert!(screen(&mut board).contains("● ✓ updated"));
+    press(&mut board, KeyCode::Char('R'));
+    wait(&mut board);
+    assert_eq!(board.message(), Some("healthchecked 1 provider"));
+    let providers = screen(&mut board);
+    assert!(providers.contains("● ✓ healthchecked"));
+    assert!(!providers.contains("✓ updated"));
+
+    press(&mut board, KeyCode::Char('m'));
+    assert!(!screen(&mut board).contains('●'));
+}
+
+#[test]
+fn test_update_diff() {
+    let (_mock, mut board) = mock_board();
+    press(&mut board, KeyCode::Tab);
+    press(&mut board, KeyCode::Char('i'));
+    assert!(screen(&mut board).contains("none since clashi started"));
+
+    press(&mut board, KeyCode::Char('f'));
+    let popup = screen(&mut board);
+    assert!(popup.contains(" Subscription A updated "));
+    assert!(popup.contains("+1 added  -1 removed  3 kept"));
+    assert!(popup.contains("+ US 01"));
+    assert!(popup.contains("- HK 01"));
+    assert!(popup.contains("  HK 02"));
+    assert!(board.key_hints().to_string().ends_with("other keys close"));
+
+    // scrolling keeps it, other keys close it
+    press(&mut board, KeyCode::Char('j'));
+    assert!(screen(&mut board).contains(" Subscription A updated "));
+    press(&mut board, KeyCode::Char('x'));
+    let detail = screen(&mut board);
+    assert!(!detail.contains(" Subscription A updated "));
+    assert_eq!(detail.matches("+1 added  -1 removed  3 kept").count(), 1);
+
+    // the next update swaps the next proxy
+    press(&mut board, KeyCode::Char('f'));
+    let popup = screen(&mut board);
+    assert!(popup.contains("+ US 02"));
+    assert!(popup.contains("- HK 02"));
+    press(&mut board, KeyCode::Esc);
+    assert_eq!(
+        screen(&mut board)
+            .matches("+1 added  -1 removed  3 kept")
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn test_provider_proxies_used_by() {
+    let client = memory_controller();
+    let mut board = BoardWidget::new(Store::new(client).unwrap());
+    press(&mut board, KeyCode::Tab);
+    press(&mut board, KeyCode::Enter);
+    let proxies = screen(&mut board);
+    assert!(proxies.contains("Used by"));
+    // HK 01 and HK 02 are both in Auto, Balance and Proxy, only HK 01 is the `now` of a group
+    let rows: Vec<String> = proxies
+        .chars()
+        .collect::<Vec<_>>()
+        .chunks(100)
+        .map(|row| row.iter().collect())
+        .collect();
+    let row = |name: &str| rows.iter().find(|row| row.contains(name)).unwrap();
+    assert!(row("HK 01").contains("● HK 01"));
+    assert!(row("HK 01").contains("Auto, Balance, Proxy"));
+    assert!(!row("HK 02").contains('●'));
+    assert!(row("HK 02").contains("Auto, Balance, Proxy"));
+
+    // groups keep their own proxy page
+    press(&mut board, KeyCode::Tab);
+    press(&mut board, KeyCode::Enter);
+    assert!(!screen(&mut board).contains("Used by"));
+}
+
+#[test]
+fn test_select_from_provider_proxies() {
+    let client = memory_controller();
+    let mut board = BoardWidget::new(Store::new(client.clone()).unwrap());
+    press(&mut board, KeyCode::Tab);
+    press(&mut board, KeyCode::Enter);
+
+    // HK 01 is in a single selector group
+    press(&mut board, KeyCode::Enter);
+    let picker = screen(&mut board);
+    assert!(picker.contains(" Use HK 01 in "));
+    assert!(picker.contains("Proxy               now Auto"));
+    assert_eq!(
+        board.key_hints().to_string(),
+        "j/↓/k/↑ select  enter/space use  esc close"
+    );
+    press(&mut board, KeyCode::Enter);
+    let groups = client.proxy_groups().unwrap();
+    let proxy = groups.iter().find(|g| g.name == "Proxy").unwrap();
+    assert_eq!(proxy.now.as_deref(), Some("HK 01"));
+    assert!(!screen(&mut board).contains(" Use HK 01 in "));
+
+    // a double click does what enter does
+    let proxies: Vec<char> = screen(&mut board).chars().collect();
+    let at = proxies
+        .windows(5)
+        .position(|w| w.iter().collect::<String>() == "HK 01")
+        .unwrap();
+    let (column, row) = ((at % 100) as u16, (at / 100) as u16);
+    for _ in 0..2 {
+        board.mouse_event(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        });
+    }
+    assert!(screen(&mut board).contains(" Use HK 01 in "));
+    press(&mut board, KeyCode::Esc);
+    assert!(!screen(&mut board).contains(" Use HK 01 in "));
+
+    // SG 01 of Subscription B is only in automatic groups
+    press(&mut board, KeyCode::Esc);
+    press(&mut board, KeyCode::Char('l'));
+    press(&mut board, KeyCode::Enter);
+    press(&mut board, KeyCode::Char('G'));
+    press(&mut board, KeyCode::Enter);
+    assert_eq!(board.message(), Some("no selector group has SG 01"));
+}
+
+#[test]
+fn test_latency_sweep() {
+    let client = memory_controller();
+    let mut board = BoardWidget::new(Store::new(client).unwrap());
+    draw(&mut board);
+    for _ in 0..5 {
+        press(&mut board, KeyCode::Right);
+    }
+    press(&mut board, KeyCode::Enter);
+
+    // the results show up as they come
+    press(&mut board, KeyCode::Char('T'));
+    assert!(board.is_busy());
+    let sweeping = screen(&mut board);
+    assert!(sweeping.contains("Latency 0/5"));
+    assert!(sweeping.contains('…'));
+    wait(&mut board);
+    assert_eq!(board.message(), Some("tested 5 proxies, 0 failed"));
+    let swept = screen(&mut board);
+    assert!(!swept.contains("Latency 0/5") && !swept.contains('…'));
+
+    // esc stops the sweep and stays on the page
+    press(&mut board, KeyCode::Char('T'));
+    press(&mut board, KeyCode::Esc);
+    assert_eq!(board.message(), Some("sweep cancelled at 0/5"));
+    assert!(!board.is_busy());
+    assert!(screen(&mut board).contains("Latency"));
+    press(&mut board, KeyCode::Esc);
+    assert!(!screen(&mut board).contains("Latency"));
+}
+
+#[test]
+fn test_bulk_without_providers() {
+    let groups = memory_controller().proxy_groups().unwrap();
+    let client = Arc::new(MemoryController::new(groups, vec![]));
+    let mut board = BoardWidget::new(Store::new(client).unwrap());
+    press(&mut board, KeyCode::Tab);
+    press(&mut board, KeyCode::Char('F'));
+    assert_eq!(board.message(), Some("no providers to update"));
+    assert!(!board.is_busy());
+    press(&mut board, KeyCode::Char('R'));
+    assert_eq!(board.message(), Some("no providers to healthcheck"));
+}
@@ -0,0 +1,54 @@
+use clashi::{
+    backend::{ControllerClient, Error, HttpController, snapshot},
+    mock::{MockConfig, MockController},
+};
+
+#[test]
+fn test_snapshot_round_trip() {
+    let mock = MockController::start(MockConfig::default()).unwrap();
+    let controller = HttpController::new(mock.url());
+    controller.select_proxy("Streaming", "JP 02").unwrap();
+
+    let dir = std::env::temp_dir().join(format!("clashi-snapshot-{}", std::process::id()));
+    snapshot::write(&controller, &dir).unwrap();
+    drop(mock);
+
+    let snapshot = snapshot::open(&dir).unwrap();
+    assert!(snapshot.is_read_only());
+
+    let groups = snapshot.proxy_groups().unwrap();
+    let streaming = groups.iter().find(|g| g.name == "Streaming").unwrap();
+    assert_eq!(streaming.now.as_deref(), Some("JP 02"));
+    assert_eq!(snapshot.proxy_providers().unwrap().len(), 2);
+
+    assert!(matches!(
+        snapshot.select_proxy("Streaming", "US 01"),
+        Err(Error::ReadOnly)
+    ));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_snapshot_with_dangling_member() {
+    let mock = MockController::start(MockConfig::default()).unwrap();
+    let controller = HttpController::new(mock.url());
+    let dir = std::env::temp_dir().join(format!("clashi-dangling-{}", std::process::id()));
+    snapshot::write(&controller, &dir).unwrap();
+    drop(mock);
+
+    // trim a proxy that groups still list
+    let path = dir.join(snapshot::PROXIES_FILE);
+    let mut proxies: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    proxies["proxies"].as_object_mut().unwrap().remove("SG 01");
+    std::fs::write(&path, proxies.to_string()).unwrap();
+
+    let error = snapshot::open(&dir).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    let message = error.to_string();
+    assert!(message.contains(snapshot::PROXIES_FILE));
+    assert!(message.contains("group `") && message.contains("has `SG 01`, which is not a proxy"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}